@@ -1,87 +1,818 @@
 #![recursion_limit = "8192"]
 
-use std::collections::HashSet;
+#[cfg(test)]
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::io::{Cursor, Write};
+#[cfg(test)]
+use std::collections::VecDeque;
+#[cfg(test)]
+use std::rc::Rc;
 
 use chrono::prelude::*;
 use failure::{Error, format_err};
+use http::header::{HeaderName, HeaderValue};
 use serde_derive::{Deserialize, Serialize};
-use yew::{Component, ComponentLink, Html, html::ChangeData, Renderable, ShouldRender};
-use yew::format::{Json, Nothing};
+use stdweb::{js, Once, Value};
+use stdweb::unstable::TryInto;
+use stdweb::web::event::LoadEndEvent;
+use stdweb::traits::{IDragEvent, IEvent, IKeyboardEvent};
+use stdweb::web::{window, FileReader, FileReaderResult, IEventTarget, Storage, TypedArray};
+use yew::{Callback, Component, ComponentLink, Html, html::ChangeData, Renderable, ShouldRender};
+use yew::format::{Binary, Json, Nothing, Text};
 use yew::html;
 use yew::services::console::ConsoleService;
-use yew::services::fetch::{FetchService, FetchTask, Request, Response};
+use yew::services::fetch::{FetchService, FetchTask, Request, Response, StatusCode};
 use yew::services::interval::{IntervalService, IntervalTask};
-use yew::services::reader::{File, FileData, ReaderService, ReaderTask};
-use yew::services::storage::{Area, StorageService};
+use yew::services::reader::{File, FileData, IBlob};
+use yew::services::timeout::{TimeoutService, TimeoutTask};
 use yew::virtual_dom::VNode;
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
 
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+const BUILD_TIMESTAMP: &str = env!("BUILD_TIMESTAMP");
 const KEY: &str = "violetear.web-client.database";
+const SETTINGS_KEY: &str = "violetear.web-client.settings";
+const CONFIG_FETCH_ATTEMPTS: u32 = 3;
+const CONFIG_FETCH_RETRY_DELAY_MS: u64 = 1500;
+const PROFILE_CONFIG_PREVIEW_LEN: usize = 4096;
+const DEFAULT_POLL_INTERVAL_MS: u64 = 1000;
+const DEFAULT_IDLE_WARNING_SECONDS: u64 = 60;
+const DEFAULT_STALE_DEFINITIONS_DAYS: i64 = 7;
+const DEFAULT_CREATE_REPORT_PATH: &str = "/v1/reports/create";
+const DEFAULT_TASKS_PATH: &str = "/v1/reports/{report_id}/tasks";
+const DEFAULT_PROFILES_PATH: &str = "/v1/profiles";
+const MAX_RECONNECT_ATTEMPTS: u32 = 5;
+const RECONNECT_DELAY_MS: u64 = 2000;
+const MAINTENANCE_RETRY_MS: u64 = 30_000;
+const LOGOUT_TIMEOUT_MS: u64 = 5000;
+const WELCOME_BACK_MS: u64 = 5000;
+const MAX_QUERY_URI_LEN: usize = 2000;
+const MAX_BUNDLE_FILE_BYTES: usize = 64 * 1024 * 1024;
+const BASE_TITLE: &str = "Violetear";
+const MAX_POLL_ATTEMPTS: u32 = 300;
 
-struct Model {
-    link: ComponentLink<Model>,
-    storage_service: StorageService,
-    fetch_service: FetchService,
+struct Model<A: Api = FetchService> {
+    link: MessageLink<Self>,
+    storage_service: LocalStore,
+    is_storage_notice_dismissed: bool,
+    storage_write_warning: Option<String>,
+    fetch_service: A,
     console_service: ConsoleService,
-    reader_service: ReaderService,
     interval_service: IntervalService,
-    ft: Option<FetchTask>,
+    timeout_service: TimeoutService,
+    ft: Option<A::Task>,
+    ft_label: &'static str,
+    report_ft: Option<A::Task>,
+    lookup_ft: Option<A::Task>,
+    limits_ft: Option<A::Task>,
+    request_id: String,
+    refresh_ft: Option<A::Task>,
+    background_fts: HashMap<i64, A::Task>,
+    health_ft: Option<A::Task>,
+    last_request: Option<RecordedRequest>,
     config: Option<Config>,
+    config_fetch_attempts: u32,
+    modal: Option<Modal>,
     state: State,
+    settings: Settings,
     scene: Scene,
     loginregister_error: Option<String>,
     loginregister_form: LoginRegisterFormData,
-    logout_error: Option<String>,
+    auth_view: AuthView,
+    password_reset_form: PasswordResetFormData,
+    password_reset_message: Option<String>,
+    password_reset_error: Option<String>,
+    is_password_reset_loading: bool,
+    logout_warning: Option<String>,
+    logout_everywhere_error: Option<String>,
     fetched_profiles: Option<ProfilesResponse>,
     fetch_profiles_error: Option<String>,
-    enabled_profiles: HashSet<String>,
+    enabled_profiles: HashSet<i64>,
+    show_detections_only: bool,
+    show_muted_profiles: bool,
+    group_by_severity: bool,
+    is_clean_expanded: bool,
+    dragged_profile_id: Option<i64>,
+    highlighted_profile_id: Option<i64>,
+    is_mobile: bool,
+    embed_mode: bool,
+    expanded_profile_configs: HashSet<i64>,
+    config_override_drafts: HashMap<String, String>,
+    config_overrides: HashMap<String, serde_json::Value>,
+    config_override_errors: HashMap<String, String>,
+    full_profile_configs: HashSet<i64>,
+    is_large_selection_acknowledged: bool,
     is_file_uploading: bool,
+    is_url_submitting: bool,
+    is_retry_failed_loading: bool,
+    is_report_creating: bool,
+    scan_url: String,
+    scan_input_kind: InputKind,
+    lookup_hash: String,
+    lookup_reports: Option<Vec<Report>>,
+    lookup_error: Option<String>,
+    is_lookup_loading: bool,
+    is_connection_testing: bool,
+    connection_test_result: Option<Result<(), String>>,
+    create_report_error: Option<String>,
+    create_report_rejection: Option<CreateReportRejection>,
     is_register_disabled: bool,
     is_register_loading: bool,
     is_login_loading: bool,
     is_login_disabled: bool,
+    is_guest_login_loading: bool,
+    is_guest: bool,
+    session_restored: bool,
+    welcome_back_tt: Option<TimeoutTask>,
     is_logout_loading: bool,
     is_logout_disabled: bool,
+    is_logout_everywhere_loading: bool,
+    current_report_id: Option<i64>,
+    current_report_hash: Option<String>,
+    current_report_created_when: Option<DateTime<Utc>>,
+    previous_verdicts: Option<HashMap<i64, String>>,
+    verdict_cache: HashMap<String, HashMap<i64, String>>,
     current_pending_tasks: Option<Vec<Task>>,
-    rt: Option<ReaderTask>,
+    previous_report: Option<PreviousReport>,
+    report_tasks: HashMap<i64, Vec<Task>>,
+    is_viewing_previous_report: bool,
+    poll_attempts: u32,
+    poll_interval_ms: u64,
+    reconnect_attempts: u32,
+    is_reconnecting: bool,
+    is_connection_lost: bool,
+    paused_report_id: Option<i64>,
+    manual_poll_report_id: Option<i64>,
+    is_status_rollup_unavailable: bool,
+    is_polling_fetch_in_flight: bool,
+    tasks_fetch_seq: u64,
+    applied_tasks_seq: u64,
+    timed_out_task_ids: HashSet<i64>,
+    is_report_unavailable: bool,
+    scan_announcement: String,
+    pending_file_name: Option<String>,
+    pending_file: Option<Vec<u8>>,
+    /// The bytes of the latest upload and its report id, kept for "Download bundle".
+    bundle_file: Option<(i64, Vec<u8>)>,
+    session_reports: Vec<SessionReport>,
+    audit_log: Vec<AuditEntry>,
+    history_reports: Option<Vec<Report>>,
+    history_error: Option<String>,
+    upload_limits: Option<UploadLimits>,
+    history_hash_filter: String,
+    history_date_from: Option<NaiveDate>,
+    history_date_to: Option<NaiveDate>,
+    rt: Option<FileReader>,
     it: Option<IntervalTask>,
+    tt: Option<TimeoutTask>,
+    logout_tt: Option<TimeoutTask>,
+    copied_tt: Option<TimeoutTask>,
+    idle_it: Option<IntervalTask>,
+    profiles_refresh_it: Option<IntervalTask>,
+    idle_deadline: Option<DateTime<Utc>>,
+    is_idle_warning_shown: bool,
+    maintenance_it: Option<IntervalTask>,
+    background_it: Option<IntervalTask>,
+    maintenance_message: Option<String>,
+    beforeunload_listener: Option<Value>,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
 enum Scene {
     Loading,
     LoginRegister,
     FetchConfigError,
+    Maintenance,
     LoggedIn,
+    History,
+}
+
+struct Modal {
+    title: String,
+    body: String,
+    actions: Vec<ModalAction>,
+}
+
+struct ModalAction {
+    label: &'static str,
+    class: &'static str,
+    msg: fn() -> Msg,
+}
+
+/// Steps of a scan as shown by the phase indicator, in order.
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+enum ScanPhase {
+    Reading,
+    Uploading,
+    Scanning,
+    Done,
+}
+
+impl ScanPhase {
+    fn label(self) -> &'static str {
+        match self {
+            ScanPhase::Reading => "Reading",
+            ScanPhase::Uploading => "Uploading",
+            ScanPhase::Scanning => "Scanning",
+            ScanPhase::Done => "Done",
+        }
+    }
+}
+
+/// What to retry after the backend answered with a 5xx.
+#[derive(Clone, Copy, PartialEq)]
+enum RetryTarget {
+    Profiles,
+    Poll,
+}
+
+/// What a scan is run on, which decides the engines that apply.
+#[derive(Clone, Copy, PartialEq)]
+enum InputKind {
+    File,
+    Url,
+}
+
+/// Whether engines of a module can scan the given input. URL modules (`url`, `url_*`,
+/// `*_url`) only look at URLs and `file` modules only at uploads. Other modules scan files,
+/// including the ones the backend downloads for a URL scan, so they apply to both.
+fn is_module_applicable(module: &str, input_kind: InputKind) -> bool {
+    let is_url_module = module == "url" || module.starts_with("url_") || module.ends_with("_url");
+    let is_file_module = module == "file" || module.starts_with("file_");
+
+    match input_kind {
+        InputKind::File => !is_url_module,
+        InputKind::Url => !is_file_module,
+    }
+}
+
+#[derive(PartialEq)]
+enum AuthView {
+    LoginRegister,
+    PasswordReset,
 }
 
 enum Msg {
     FetchConfig,
-    FetchConfigDone(Result<Config, Error>),
+    FetchConfigDone(Result<Box<Config>, Error>),
     LoginRegisterFormDataChange(LoginRegisterFormDataField, String),
+    ToggleRememberUsername,
     Login,
     LoginDone(Result<LoginResponse, Error>),
+    RefreshSessionDone(Result<RefreshResponse, Error>),
     Register,
     RegisterDone(Result<RegisterResponse, Error>),
+    SetAuthView(AuthView),
+    PasswordResetFormDataChange(String),
+    RequestPasswordReset,
+    RequestPasswordResetDone(Result<(), Error>),
+    GuestLogin,
+    GuestLoginDone(Result<GuestLoginResponse, Error>),
+    ConfirmLogout,
+    DownloadAuditLog,
     Logout,
     LogoutDone(Result<(), Error>),
+    LogoutTimedOut,
+    LogoutEverywhere,
+    LogoutEverywhereDone(Result<(), Error>),
     FetchProfiles,
     FetchProfilesDone(Result<ProfilesResponse, Error>),
-    ToggleProfile(String),
+    ToggleProfile(i64),
+    SelectAllProfiles,
+    ToggleAutoEnableNewProfiles,
+    ToggleCompletionNotifications,
+    DragProfileStart(i64),
+    DropProfile(i64),
+    ToggleProfileConfig(i64),
+    ShowFullProfileConfig(i64),
+    ConfigOverrideChange(String, String),
+    CopyProfileConfig(i64),
+    ToggleDetectionsFilter,
+    ToggleShowMutedProfiles,
+    ToggleGroupBySeverity,
+    ToggleCollapseClean,
+    ToggleCleanExpanded,
+    ToggleProfileMute(i64),
+    TogglePreviousReport,
+    SelectReportTab(i64),
+    PollBackgroundReports,
+    FetchBackgroundTasksDone(i64, Result<TasksResponse, Error>),
+    AcknowledgeLargeSelection,
     LoadFile(ChangeData),
+    PasteFile(File),
     CreateReport(FileData),
+    ReadFileFailed,
+    CancelFileRead,
     CreateReportDone(Result<CreateResponse, Error>),
+    ScanUrlChange(String),
+    LookupHashChange(String),
+    LookupHash,
+    LookupHashDone(Result<ReportsResponse, Error>),
+    ScanUrl,
+    VisibilityChanged(bool),
+    UserActivity,
+    IdleTick,
+    AutoRefreshProfiles,
+    SnoozeIdleLogout,
+    ViewportChanged(bool),
+    PollReport(i64),
+    FetchReportStatusDone(i64, Result<ReportStatus, Error>),
     FetchTasks(i64),
-    FetchTasksDone(Result<TasksResponse, Error>),
+    FetchTasksDone(u64, Result<TasksResponse, Error>),
+    ReportNotFound,
+    FetchReportDone(Result<Report, Error>),
+    SessionExpired,
+    DismissModal,
+    DismissStorageNotice,
+    DismissStorageWriteWarning,
+    TestConnection,
+    TestConnectionDone(Result<(), Error>),
+    Reload,
+    MaintenanceStarted(Option<String>),
+    ToggleDensity,
+    DismissLoginHint,
+    ShowSessionReport(i64),
+    ShowHistory,
+    CloseHistory,
+    FetchReportsDone(Result<ReportsResponse, Error>),
+    FetchLimits,
+    FetchLimitsDone(Result<UploadLimits, Error>),
+    HistoryHashFilterChange(String),
+    HistoryDateFromChange(String),
+    HistoryDateToChange(String),
+    TransientFailure(RetryTarget),
+    KeepWaiting,
+    RetryFailed,
+    RetryTask(i64),
+    RetryFailedDone(i64, Result<(), Error>),
+    DownloadPdf,
+    DownloadBundle,
+    CopyLastRequestAsCurl,
+    DownloadConfigTemplate,
+    CopyReportId(i64),
+    CopyReportIdDone(bool),
+    HideReportIdCopied,
+    HideWelcomeBack,
+    ScanAnotherSameProfiles,
+    ClearResults,
     NoOp,
+    JumpToDetection,
+}
+
+/// What kind of body the last request carried; the payload itself is not kept.
+#[derive(Clone, Copy)]
+enum RequestBody {
+    Empty,
+    Json,
+    Binary,
+}
+
+/// Parts of the last authenticated API request, kept so debug builds can replay it with curl.
+struct RecordedRequest {
+    method: &'static str,
+    url: String,
+    token: Option<String>,
+    body: RequestBody,
+}
+
+impl RecordedRequest {
+    fn new(method: &'static str, url: &str, token: &Option<String>, body: RequestBody) -> Self {
+        Self {
+            method,
+            url: url.to_owned(),
+            token: token.to_owned(),
+            body,
+        }
+    }
+
+    /// Renders the request as a curl command line. The token is replaced by a placeholder
+    /// unless `include_token` is set.
+    fn to_curl(&self, include_token: bool) -> String {
+        let mut parts = vec![
+            "curl".to_owned(),
+            "-X".to_owned(),
+            self.method.to_owned(),
+            shell_quote(&self.url),
+        ];
+
+        if let Some(token) = &self.token {
+            let token = if include_token {
+                token.as_str()
+            } else {
+                "<token>"
+            };
+            parts.push("-H".to_owned());
+            parts.push(shell_quote(&format!("Authorization: {}", token)));
+        }
+
+        match self.body {
+            RequestBody::Empty => {}
+            RequestBody::Json => {
+                parts.push("-H".to_owned());
+                parts.push(shell_quote("Content-Type: application/json"));
+                parts.push("--data".to_owned());
+                parts.push(shell_quote("@request.json"));
+            }
+            RequestBody::Binary => {
+                parts.push("--data-binary".to_owned());
+                parts.push(shell_quote("@file"));
+            }
+        }
+
+        parts.join(" ")
+    }
+}
+
+/// Prefixes the page title with the number of pending engines, so a backgrounded tab shows
+/// progress. Zero restores the base title.
+fn set_document_title(pending_count: usize) {
+    let title = if pending_count > 0 {
+        format!("({}) {}", pending_count, BASE_TITLE)
+    } else {
+        BASE_TITLE.to_owned()
+    };
+
+    js! { @(no_return)
+        document.title = @{title};
+    }
+}
+
+/// Has the browser save `content` as a file.
+fn download_text(file_name: String, mime_type: &str, content: String) {
+    download_bytes(file_name, mime_type, content.as_bytes());
+}
+
+/// Asks for permission to show notifications. A refusal is remembered by the browser and makes
+/// `show_notification` a no-op.
+fn request_notification_permission() {
+    js! { @(no_return)
+        if ("Notification" in window && Notification.permission === "default") {
+            Notification.requestPermission();
+        }
+    }
+}
+
+fn show_notification(title: &str, body: String) {
+    js! { @(no_return)
+        if ("Notification" in window && Notification.permission === "granted") {
+            new Notification(@{title}, { body: @{body} });
+        }
+    }
+}
+
+/// Plays a short beep, skipped where the Web Audio API is unavailable.
+fn play_completion_sound() {
+    js! { @(no_return)
+        var AudioContext = window.AudioContext || window.webkitAudioContext;
+        if (!AudioContext) {
+            return;
+        }
+        var context = new AudioContext();
+        var oscillator = context.createOscillator();
+        var gain = context.createGain();
+        oscillator.frequency.value = 880;
+        gain.gain.value = 0.1;
+        oscillator.connect(gain);
+        gain.connect(context.destination);
+        oscillator.start();
+        oscillator.stop(context.currentTime + 0.2);
+        oscillator.onended = function() { context.close(); };
+    }
+}
+
+fn download_bytes(file_name: String, mime_type: &str, content: &[u8]) {
+    let content: TypedArray<u8> = content.into();
+    js! { @(no_return)
+        var blob = new Blob([@{content}], { type: @{mime_type} });
+        var url = URL.createObjectURL(blob);
+        var link = document.createElement("a");
+        link.href = url;
+        link.download = @{file_name};
+        document.body.appendChild(link);
+        link.click();
+        document.body.removeChild(link);
+        URL.revokeObjectURL(url);
+    }
+}
+
+fn copy_to_clipboard(text: String) {
+    js! { @(no_return)
+        navigator.clipboard.writeText(@{text});
+    }
+}
+
+/// Like `copy_to_clipboard`, but tells whether the browser accepted the text.
+fn copy_to_clipboard_then(text: String, callback: Callback<bool>) {
+    let done = move |is_copied: bool| callback.emit(is_copied);
+    js! { @(no_return)
+        var done = @{Once(done)};
+        if (navigator.clipboard) {
+            navigator.clipboard.writeText(@{text}).then(
+                function() { done(true); },
+                function() { done(false); }
+            );
+        } else {
+            done(false);
+        }
+    }
+}
+
+/// Brings an element to the middle of the viewport and gives it focus.
+fn scroll_to_element(id: &str) {
+    js! { @(no_return)
+        var element = document.getElementById(@{id});
+        if (element) {
+            element.scrollIntoView({ behavior: "smooth", block: "center" });
+            element.focus({ preventScroll: true });
+        }
+    }
+}
+
+/// Selects the text of an element so users can copy it themselves.
+fn select_element_text(id: &str) {
+    js! { @(no_return)
+        var element = document.getElementById(@{id});
+        if (element) {
+            var range = document.createRange();
+            range.selectNodeContents(element);
+            var selection = window.getSelection();
+            selection.removeAllRanges();
+            selection.addRange(range);
+        }
+    }
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// `localStorage`, or a map that only lives as long as the page where the browser refuses
+/// storage, e.g. in strict privacy modes. Unlike `StorageService`, failed writes never panic.
+enum LocalStore {
+    Browser(Storage),
+    Memory(HashMap<String, String>),
+    /// Refuses every write, like a browser whose storage quota is used up.
+    #[cfg(test)]
+    Full,
+}
+
+impl LocalStore {
+    fn new() -> Self {
+        let is_available: bool = js! {
+            try {
+                var key = "violetear.web-client.probe";
+                window.localStorage.setItem(key, key);
+                window.localStorage.removeItem(key);
+                return true;
+            } catch (error) {
+                return false;
+            }
+        }
+        .try_into()
+        .unwrap_or(false);
+
+        if is_available {
+            LocalStore::Browser(window().local_storage())
+        } else {
+            LocalStore::Memory(HashMap::new())
+        }
+    }
+
+    fn is_persistent(&self) -> bool {
+        match self {
+            LocalStore::Browser(_) => true,
+            LocalStore::Memory(_) => false,
+            #[cfg(test)]
+            LocalStore::Full => true,
+        }
+    }
+
+    /// Fails when the value cannot be serialised or the browser refuses the write, typically
+    /// because the quota is full. Callers decide how loudly to report it.
+    fn store<T>(&mut self, key: &str, value: T) -> Result<(), Error>
+    where
+        T: Into<Text>,
+    {
+        let data = value.into()?;
+        match self {
+            LocalStore::Browser(storage) => storage
+                .insert(key, &data)
+                .map_err(|_| format_err!("local storage refused to save {}", key)),
+            LocalStore::Memory(map) => {
+                map.insert(key.to_owned(), data);
+                Ok(())
+            }
+            #[cfg(test)]
+            LocalStore::Full => Err(format_err!("local storage refused to save {}", key)),
+        }
+    }
+
+    fn restore<T>(&self, key: &str) -> T
+    where
+        T: From<Text>,
+    {
+        let data = match self {
+            LocalStore::Browser(storage) => storage.get(key),
+            LocalStore::Memory(map) => map.get(key).cloned(),
+            #[cfg(test)]
+            LocalStore::Full => None,
+        };
+        T::from(data.ok_or_else(|| format_err!("nothing stored under {}", key)))
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 struct Config {
     api_url: String,
+    #[serde(default)]
+    url_scanning_enabled: bool,
+    #[serde(default)]
+    allow_anonymous: bool,
+    #[serde(default)]
+    features: Option<HashMap<String, bool>>,
+    #[serde(default)]
+    max_recommended_profiles: Option<usize>,
+    #[serde(default)]
+    poll_interval_ms: Option<u64>,
+    #[serde(default)]
+    pause_polling_when_hidden: Option<bool>,
+    #[serde(default)]
+    auto_poll: Option<bool>,
+    #[serde(default)]
+    profiles_refresh_interval_s: Option<u64>,
+    #[serde(default)]
+    idle_logout_minutes: Option<u64>,
+    #[serde(default)]
+    idle_warning_seconds: Option<u64>,
+    #[serde(default)]
+    idle_snooze_minutes: Option<u64>,
+    #[serde(default)]
+    max_upload_bytes: Option<u64>,
+    #[serde(default)]
+    stale_definitions_days: Option<i64>,
+    #[serde(default)]
+    create_report_path: Option<String>,
+    #[serde(default)]
+    tasks_path: Option<String>,
+    #[serde(default)]
+    profiles_path: Option<String>,
+    #[serde(default)]
+    extra_headers: Option<HashMap<String, String>>,
+    #[serde(default)]
+    threat_lookup_url_template: Option<String>,
+    #[serde(default)]
+    environment: Option<String>,
+    #[serde(default)]
+    debug: bool,
+    #[serde(default)]
+    debug_include_token: bool,
+}
+
+impl Config {
+    /// A sample `config.json` for operators. Fields with a fixed default carry it, the others are
+    /// left unset; listing every field here keeps the template in step with the struct.
+    fn template() -> Self {
+        Self {
+            api_url: "https://api.example.com".into(),
+            url_scanning_enabled: false,
+            allow_anonymous: false,
+            features: Some(HashMap::new()),
+            max_recommended_profiles: None,
+            poll_interval_ms: None,
+            pause_polling_when_hidden: Some(true),
+            auto_poll: Some(true),
+            profiles_refresh_interval_s: None,
+            idle_logout_minutes: None,
+            idle_warning_seconds: Some(DEFAULT_IDLE_WARNING_SECONDS),
+            idle_snooze_minutes: None,
+            max_upload_bytes: None,
+            stale_definitions_days: Some(DEFAULT_STALE_DEFINITIONS_DAYS),
+            create_report_path: Some(DEFAULT_CREATE_REPORT_PATH.into()),
+            tasks_path: Some(DEFAULT_TASKS_PATH.into()),
+            profiles_path: Some(DEFAULT_PROFILES_PATH.into()),
+            extra_headers: Some(HashMap::new()),
+            threat_lookup_url_template: None,
+            environment: None,
+            debug: false,
+            debug_include_token: false,
+        }
+    }
+
+    fn create_report_uri(&self) -> String {
+        let path = self
+            .create_report_path
+            .as_deref()
+            .unwrap_or(DEFAULT_CREATE_REPORT_PATH);
+        format!("{}{}", self.api_url, path)
+    }
+
+    /// `tasks_path` may place the report id anywhere with a `{report_id}` placeholder.
+    fn tasks_uri(&self, report_id: i64) -> String {
+        let path = self
+            .tasks_path
+            .as_deref()
+            .unwrap_or(DEFAULT_TASKS_PATH)
+            .replace("{report_id}", &report_id.to_string());
+        format!("{}{}", self.api_url, path)
+    }
+
+    fn profiles_uri(&self) -> String {
+        let path = self
+            .profiles_path
+            .as_deref()
+            .unwrap_or(DEFAULT_PROFILES_PATH);
+        format!("{}{}", self.api_url, path)
+    }
+
+    /// The environment name to warn about, `None` for production or when unset.
+    fn non_production_environment(&self) -> Option<&str> {
+        match self.environment.as_ref().map(|x| x.trim()) {
+            None | Some("") => None,
+            Some(x) if x.eq_ignore_ascii_case("production") || x.eq_ignore_ascii_case("prod") => {
+                None
+            }
+            Some(x) => Some(x),
+        }
+    }
+
+    /// Looks up a server-driven feature flag, falling back to the dedicated config fields for
+    /// flags that predate `features`. Unknown flags are off.
+    fn feature_enabled(&self, name: &str) -> bool {
+        if let Some(enabled) = self.features.as_ref().and_then(|x| x.get(name)) {
+            return *enabled;
+        }
+
+        match name {
+            "url_scanning" => self.url_scanning_enabled,
+            "guest" => self.allow_anonymous,
+            _ => false,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize)]
 struct State {
     token: Option<String>,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    #[serde(default)]
+    report_id: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+struct Settings {
+    compact_table: bool,
+    login_hint_dismissed: bool,
+    enabled_profile_ids: Option<HashSet<i64>>,
+    remember_username: bool,
+    remembered_username: Option<String>,
+    profile_order: Vec<i64>,
+    known_profile_ids: Option<HashSet<i64>>,
+    auto_enable_new_profiles: bool,
+    last_results: HashMap<i64, String>,
+    notify_on_completion: bool,
+    muted_profile_ids: HashSet<i64>,
+    collapse_clean: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            compact_table: true,
+            login_hint_dismissed: false,
+            enabled_profile_ids: None,
+            remember_username: false,
+            remembered_username: None,
+            profile_order: Vec::new(),
+            known_profile_ids: None,
+            auto_enable_new_profiles: false,
+            last_results: HashMap::new(),
+            notify_on_completion: false,
+            muted_profile_ids: HashSet::new(),
+            collapse_clean: false,
+        }
+    }
+}
+
+/// One line of the session log users can download for their records. Kept in memory only.
+struct AuditEntry {
+    timestamp: DateTime<Utc>,
+    action: String,
+}
+
+/// The last completed report, kept when a new scan starts so users can switch back to it.
+struct PreviousReport {
+    report_id: i64,
+    tasks: Vec<Task>,
+}
+
+struct SessionReport {
+    report_id: i64,
+    file_name: String,
+    summary: Option<String>,
 }
 
 enum LoginRegisterFormDataField {
@@ -95,14 +826,41 @@ struct LoginRegisterFormData {
     password: String,
 }
 
+#[derive(Serialize, Default)]
+struct PasswordResetFormData {
+    username: String,
+}
+
 #[derive(Deserialize)]
 struct LoginResponse {
     token: Option<String>,
+    #[serde(default)]
+    refresh_token: Option<String>,
 }
 
 #[derive(Deserialize)]
 struct RegisterResponse {
     token: Option<String>,
+    #[serde(default)]
+    refresh_token: Option<String>,
+}
+
+#[derive(Serialize)]
+struct RefreshRequest<'a> {
+    refresh_token: &'a str,
+}
+
+/// A new access token, and a new refresh token when the backend rotates them.
+#[derive(Deserialize)]
+struct RefreshResponse {
+    token: String,
+    #[serde(default)]
+    refresh_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GuestLoginResponse {
+    token: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -112,6 +870,11 @@ pub struct Profile {
     pub human_name: String,
     pub module: String,
     pub config: Option<serde_json::Value>,
+    #[serde(default)]
+    pub recommended: Option<bool>,
+    /// When the engine's signature definitions were last updated, if the backend knows.
+    #[serde(default)]
+    pub definitions_date: Option<DateTime<Utc>>,
 }
 
 #[derive(Deserialize)]
@@ -124,21 +887,86 @@ pub struct TasksResponse {
     tasks: Vec<Task>,
 }
 
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportStatus {
+    Pending,
+    Complete,
+    Failed,
+}
+
+#[derive(Deserialize)]
+pub struct ReportStatusResponse {
+    status: ReportStatus,
+}
+
+#[derive(Serialize)]
+struct CreateUrlReportRequest {
+    url: String,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    overrides: HashMap<String, serde_json::Value>,
+}
+
+/// An uploaded file with config overrides, which the raw file body has no room for.
+#[derive(Serialize)]
+struct CreateFileReportRequest {
+    /// The file content in base64.
+    file: String,
+    overrides: HashMap<String, serde_json::Value>,
+}
+
 #[derive(Deserialize)]
 pub struct CreateResponse {
     report_id: i64,
 }
 
+/// Quota usage the backend may include when it refuses a new report.
+#[derive(Deserialize, Default, Clone, Debug)]
+struct QuotaUsage {
+    #[serde(default)]
+    used: Option<u64>,
+    #[serde(default)]
+    limit: Option<u64>,
+}
+
+/// Upload refusals that deserve their own explanation rather than the generic error.
+#[derive(Clone, Debug)]
+enum CreateReportRejection {
+    TooLarge,
+    QuotaExceeded(QuotaUsage),
+}
+
+impl std::fmt::Display for CreateReportRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CreateReportRejection::TooLarge => write!(f, "file too large"),
+            CreateReportRejection::QuotaExceeded(_) => write!(f, "quota exceeded"),
+        }
+    }
+}
+
+impl std::error::Error for CreateReportRejection {}
+
+#[derive(Default, Deserialize)]
+pub struct UploadLimits {
+    #[serde(default)]
+    max_upload_bytes: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct ReportsResponse {
+    reports: Vec<Report>,
+}
+
 #[derive(Deserialize)]
 pub struct Report {
     pub id: i64,
     pub user_id: i64,
     pub created_when: chrono::DateTime<Utc>,
     pub file_multihash: String,
-    pub file: Option<Vec<u8>>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Task {
     id: i64,
     report_id: i64,
@@ -147,58 +975,192 @@ pub struct Task {
     completed_when: Option<chrono::DateTime<Utc>>,
     status: String,
     message: Option<String>,
+    #[serde(default)]
+    queue_position: Option<u32>,
+}
+
+/// Where `Model` sends messages to itself: the yew component link in the app, a queue the test
+/// drains into `update` otherwise.
+enum MessageLink<COMP: Component> {
+    Component(ComponentLink<COMP>),
+    #[cfg(test)]
+    Queue(Rc<RefCell<VecDeque<COMP::Message>>>),
+}
+
+impl<COMP: Component + Renderable<COMP>> MessageLink<COMP> {
+    fn send_back<F, IN>(&mut self, function: F) -> Callback<IN>
+    where
+        F: Fn(IN) -> COMP::Message + 'static,
+    {
+        match self {
+            MessageLink::Component(link) => link.send_back(function),
+            #[cfg(test)]
+            MessageLink::Queue(queue) => {
+                let queue = queue.clone();
+                Callback::from(move |input| queue.borrow_mut().push_back(function(input)))
+            }
+        }
+    }
+
+    fn send_self(&mut self, msg: COMP::Message) {
+        match self {
+            MessageLink::Component(link) => link.send_self(msg),
+            #[cfg(test)]
+            MessageLink::Queue(queue) => queue.borrow_mut().push_back(msg),
+        }
+    }
+}
+
+/// The HTTP layer `Model` talks to the backend through. `FetchService` is the real
+/// implementation; a mock can be substituted to drive the component without a backend.
+trait Api: Default + 'static {
+    type Task: yew::services::Task;
+
+    fn fetch<IN, OUT>(
+        &mut self,
+        request: Request<IN>,
+        callback: Callback<Response<OUT>>,
+    ) -> Self::Task
+    where
+        IN: Into<Text>,
+        OUT: From<Text> + 'static;
+
+    fn fetch_binary<IN, OUT>(
+        &mut self,
+        request: Request<IN>,
+        callback: Callback<Response<OUT>>,
+    ) -> Self::Task
+    where
+        IN: Into<Binary>,
+        OUT: From<Binary> + 'static;
+}
+
+impl Api for FetchService {
+    type Task = FetchTask;
+
+    fn fetch<IN, OUT>(
+        &mut self,
+        request: Request<IN>,
+        callback: Callback<Response<OUT>>,
+    ) -> FetchTask
+    where
+        IN: Into<Text>,
+        OUT: From<Text> + 'static,
+    {
+        FetchService::fetch(self, request, callback)
+    }
+
+    fn fetch_binary<IN, OUT>(
+        &mut self,
+        request: Request<IN>,
+        callback: Callback<Response<OUT>>,
+    ) -> FetchTask
+    where
+        IN: Into<Binary>,
+        OUT: From<Binary> + 'static,
+    {
+        FetchService::fetch_binary(self, request, callback)
+    }
 }
 
-impl Component for Model {
+impl<A: Api> Component for Model<A> {
     type Message = Msg;
     type Properties = ();
 
     fn create(_: Self::Properties, mut link: ComponentLink<Self>) -> Self {
-        let storage_service = StorageService::new(Area::Local);
+        // Files pasted anywhere on the page are scanned like dropped ones.
+        let paste_callback = link.send_back(Msg::PasteFile);
+        let on_paste = move |file: File| paste_callback.emit(file);
+        js! { @(no_return)
+            var on_paste = @{on_paste};
+            document.addEventListener("paste", function(event) {
+                var files = event.clipboardData && event.clipboardData.files;
+                if (files && files.length === 1) {
+                    event.preventDefault();
+                    on_paste(files[0]);
+                }
+            });
+        }
 
-        let state = {
-            if let Json(Ok(state)) = storage_service.restore(KEY) {
-                state
-            } else {
-                State { token: None }
-            }
-        };
+        // Polling pauses while the tab is hidden, see `Msg::VisibilityChanged`.
+        let visibility_callback = link.send_back(Msg::VisibilityChanged);
+        let on_visibility_change = move |hidden: bool| visibility_callback.emit(hidden);
+        js! { @(no_return)
+            var on_visibility_change = @{on_visibility_change};
+            document.addEventListener("visibilitychange", function() {
+                on_visibility_change(document.hidden);
+            });
+        }
 
-        link.send_self(Msg::FetchConfig);
+        // "j" jumps to the next detection, unless the user is typing somewhere.
+        let jump_callback = link.send_back(|_: ()| Msg::JumpToDetection);
+        let on_jump = move || jump_callback.emit(());
+        js! { @(no_return)
+            var on_jump = @{on_jump};
+            document.addEventListener("keydown", function(event) {
+                var target = event.target;
+                var is_typing = target.isContentEditable
+                    || ["INPUT", "TEXTAREA", "SELECT"].indexOf(target.tagName) !== -1;
+                if (event.key === "j" && !is_typing && !event.ctrlKey && !event.metaKey && !event.altKey) {
+                    on_jump();
+                }
+            });
+        }
 
-        Self {
-            link,
-            state,
-            fetch_service: FetchService::new(),
-            console_service: ConsoleService::new(),
-            reader_service: ReaderService::new(),
-            interval_service: IntervalService::new(),
-            storage_service,
-            scene: Scene::Loading,
-            ft: None,
-            rt: None,
-            it: None,
-            config: None,
-            loginregister_error: None,
-            loginregister_form: LoginRegisterFormData::default(),
-            logout_error: None,
-            fetched_profiles: None,
-            fetch_profiles_error: None,
-            enabled_profiles: HashSet::new(),
-            is_file_uploading: false,
-            is_register_disabled: false,
-            is_register_loading: false,
-            is_login_loading: false,
-            is_login_disabled: false,
-            is_logout_loading: false,
-            is_logout_disabled: false,
-            current_pending_tasks: None,
+        // Reports activity at most every few seconds, see `Msg::UserActivity`.
+        let activity_callback = link.send_back(|_: ()| Msg::UserActivity);
+        let on_activity = move || activity_callback.emit(());
+        js! { @(no_return)
+            var on_activity = @{on_activity};
+            var last_reported = 0;
+            ["mousemove", "keydown", "click", "touchstart", "scroll"].forEach(function(type) {
+                document.addEventListener(type, function() {
+                    var now = Date.now();
+                    if (now - last_reported > 5000) {
+                        last_reported = now;
+                        on_activity();
+                    }
+                }, { passive: true });
+            });
+        }
+
+        // Switches the profiles table to stacked cards below Bulma's mobile breakpoint.
+        let viewport_callback = link.send_back(Msg::ViewportChanged);
+        let on_viewport_change = move |is_mobile: bool| viewport_callback.emit(is_mobile);
+        let is_mobile: bool = js! {
+            var on_viewport_change = @{on_viewport_change};
+            var query = window.matchMedia("(max-width: 768px)");
+            query.addListener(function(query) {
+                on_viewport_change(query.matches);
+            });
+            return query.matches;
+        }
+        .try_into()
+        .unwrap_or(false);
+
+        // `?embed=1` drops the page chrome for integrators showing the app in an iframe.
+        let embed_mode: bool = js! {
+            return new URLSearchParams(window.location.search).get("embed") === "1";
         }
+        .try_into()
+        .unwrap_or(false);
+
+        link.send_self(Msg::FetchConfig);
+
+        Self::new(
+            MessageLink::Component(link),
+            LocalStore::new(),
+            is_mobile,
+            embed_mode,
+        )
     }
 
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
         match msg {
             Msg::FetchConfig => {
+                self.tt = None;
+                self.config_fetch_attempts += 1;
+
                 self.ft =
                     Some(self.fetch_service.fetch(
                         Request::get("/config.json").body(Nothing).unwrap(),
@@ -206,7 +1168,7 @@ impl Component for Model {
                             move |response: Response<Json<Result<Config, Error>>>| {
                                 let (meta, Json(data)) = response.into_parts();
                                 if meta.status.is_success() {
-                                    Msg::FetchConfigDone(data)
+                                    Msg::FetchConfigDone(data.map(Box::new))
                                 } else {
                                     Msg::FetchConfigDone(Err(format_err!(
                                         "{}: could not fetch /config.json",
@@ -216,28 +1178,63 @@ impl Component for Model {
                             },
                         ),
                     ));
-                false
+                self.config_fetch_attempts > 1
             }
-            Msg::FetchConfigDone(Ok(response)) => {
-                self.config = Some(response);
+            Msg::FetchConfigDone(Ok(mut response)) => {
+                let base_uri: String = js! { return document.baseURI; }
+                    .try_into()
+                    .unwrap_or_default();
+
+                match normalize_api_url(&resolve_api_url(&response.api_url, &base_uri)) {
+                    Ok(api_url) => response.api_url = api_url,
+                    Err(error) => {
+                        self.show_config_error(Some(error.to_string()));
+                        return true;
+                    }
+                }
+
+                let effective_type: Option<String> = js! {
+                    var connection = navigator.connection;
+                    return connection ? connection.effectiveType : null;
+                }
+                .try_into()
+                .ok();
+
+                self.poll_interval_ms = response
+                    .poll_interval_ms
+                    .or_else(|| poll_interval_for_connection(effective_type.as_deref()))
+                    .unwrap_or(DEFAULT_POLL_INTERVAL_MS);
+
+                self.config = Some(*response);
 
                 self.console_service
                     .log(&format!("Configuration was fetched.\n{:#?}", self.config));
 
                 if self.state.token.is_some() {
+                    self.transition(Scene::Loading);
                     self.link.send_self(Msg::FetchProfiles);
-                    self.scene = Scene::Loading;
                 } else {
-                    self.scene = Scene::LoginRegister;
+                    self.transition(Scene::LoginRegister);
                 }
                 true
             }
             Msg::FetchConfigDone(Err(_)) => {
-                self.scene = Scene::FetchConfigError;
+                if self.config_fetch_attempts < CONFIG_FETCH_ATTEMPTS {
+                    self.tt = Some(self.timeout_service.spawn(
+                        std::time::Duration::from_millis(CONFIG_FETCH_RETRY_DELAY_MS),
+                        self.link.send_back(|_| Msg::FetchConfig),
+                    ));
+                    return false;
+                }
+
+                self.show_config_error(None);
                 true
             }
             Msg::Login => {
+                self.claim_fetch_slot("Login");
+
                 self.loginregister_error = None;
+                self.logout_warning = None;
                 self.is_register_disabled = true;
                 self.is_login_loading = true;
                 self.is_login_disabled = true;
@@ -245,12 +1242,15 @@ impl Component for Model {
                 if let Some(config) = &self.config {
                     self.ft = Some(
                         self.fetch_service.fetch(
-                            Request::builder()
-                                .method("POST")
-                                .uri(&format!("{}/v1/auth/login", config.api_url))
-                                .header("Content-Type", "application/json")
-                                .body(Json(&self.loginregister_form))
-                                .unwrap(),
+                            request_builder(
+                                config,
+                                "POST",
+                                &format!("{}/v1/auth/login", config.api_url),
+                                &self.request_id,
+                            )
+                            .header("Content-Type", "application/json")
+                            .body(Json(&self.loginregister_form))
+                            .unwrap(),
                             self.link.send_back(
                                 move |response: Response<Json<Result<LoginResponse, Error>>>| {
                                     let (meta, Json(data)) = response.into_parts();
@@ -271,7 +1271,10 @@ impl Component for Model {
             }
             Msg::LoginDone(Ok(login_response)) => {
                 self.state.token = Some(login_response.token.unwrap());
-                self.storage_service.store(KEY, Json(&self.state));
+                self.state.refresh_token = login_response.refresh_token;
+                self.persist_state();
+                self.persist_remembered_username();
+                self.audit(format!("Logged in as {}", self.loginregister_form.username));
                 self.is_register_disabled = false;
                 self.is_login_loading = false;
                 self.is_login_disabled = false;
@@ -286,7 +1289,10 @@ impl Component for Model {
                 true
             }
             Msg::Register => {
+                self.claim_fetch_slot("Register");
+
                 self.loginregister_error = None;
+                self.logout_warning = None;
                 self.is_register_disabled = true;
                 self.is_register_loading = true;
                 self.is_login_disabled = true;
@@ -294,12 +1300,15 @@ impl Component for Model {
                 if let Some(config) = &self.config {
                     self.ft = Some(
                         self.fetch_service.fetch(
-                            Request::builder()
-                                .method("POST")
-                                .uri(&format!("{}/v1/auth/register", config.api_url))
-                                .header("Content-Type", "application/json")
-                                .body(Json(&self.loginregister_form))
-                                .unwrap(),
+                            request_builder(
+                                config,
+                                "POST",
+                                &format!("{}/v1/auth/register", config.api_url),
+                                &self.request_id,
+                            )
+                            .header("Content-Type", "application/json")
+                            .body(Json(&self.loginregister_form))
+                            .unwrap(),
                             self.link.send_back(
                                 move |response: Response<Json<Result<RegisterResponse, Error>>>| {
                                     let (meta, Json(data)) = response.into_parts();
@@ -323,7 +1332,13 @@ impl Component for Model {
                 self.is_register_loading = false;
                 self.is_login_disabled = false;
                 self.state.token = Some(register_response.token.unwrap());
-                self.storage_service.store(KEY, Json(&self.state));
+                self.state.refresh_token = register_response.refresh_token;
+                self.persist_state();
+                self.persist_remembered_username();
+                self.audit(format!(
+                    "Registered and logged in as {}",
+                    self.loginregister_form.username
+                ));
                 self.link.send_self(Msg::FetchProfiles);
                 true
             }
@@ -334,42 +1349,46 @@ impl Component for Model {
                 self.loginregister_error = Some("Could not register".into());
                 true
             }
-            Msg::LoginRegisterFormDataChange(field, value) => {
-                match field {
-                    LoginRegisterFormDataField::Username => {
-                        self.loginregister_form.username = value;
-                    }
-                    LoginRegisterFormDataField::Password => {
-                        self.loginregister_form.password = value;
-                    }
-                }
+            Msg::SetAuthView(auth_view) => {
+                self.auth_view = auth_view;
+                self.loginregister_error = None;
+                self.password_reset_message = None;
+                self.password_reset_error = None;
+                true
+            }
+            Msg::PasswordResetFormDataChange(value) => {
+                self.password_reset_form.username = value;
                 false
             }
-            Msg::Logout => {
+            Msg::RequestPasswordReset => {
+                self.claim_fetch_slot("RequestPasswordReset");
+
                 if let Some(config) = &self.config {
-                    self.logout_error = None;
-                    self.is_logout_disabled = true;
-                    self.is_logout_loading = true;
+                    self.password_reset_message = None;
+                    self.password_reset_error = None;
+                    self.is_password_reset_loading = true;
 
                     self.ft = Some(
                         self.fetch_service.fetch(
-                            Request::builder()
-                                .method("POST")
-                                .uri(&format!("{}/v1/auth/logout", config.api_url))
-                                .header("Content-Type", "application/json")
-                                .header(
-                                    "Authorization",
-                                    self.state.token.as_ref().unwrap().to_owned(),
-                                )
-                                .body(Nothing)
-                                .unwrap(),
+                            request_builder(
+                                config,
+                                "POST",
+                                &format!("{}/v1/auth/reset-request", config.api_url),
+                                &self.request_id,
+                            )
+                            .header("Content-Type", "application/json")
+                            .body(Json(&self.password_reset_form))
+                            .unwrap(),
                             self.link.send_back(move |response: Response<Nothing>| {
                                 let (meta, _) = response.into_parts();
-                                if meta.status.is_success() {
-                                    Msg::LogoutDone(Ok(()))
+                                // An unknown account is reported like a success so the form
+                                // cannot be used to enumerate accounts.
+                                if meta.status.is_success() || meta.status == StatusCode::NOT_FOUND
+                                {
+                                    Msg::RequestPasswordResetDone(Ok(()))
                                 } else {
-                                    Msg::LogoutDone(Err(format_err!(
-                                        "{}: could not logout",
+                                    Msg::RequestPasswordResetDone(Err(format_err!(
+                                        "{}: could not request password reset",
                                         meta.status
                                     )))
                                 }
@@ -379,42 +1398,48 @@ impl Component for Model {
                 };
                 true
             }
-            Msg::LogoutDone(Ok(_)) => {
-                self.is_logout_disabled = false;
-                self.is_logout_loading = false;
-                self.state.token = None;
-                self.storage_service.store(KEY, Json(&self.state));
-                self.loginregister_error = None;
-                self.scene = Scene::LoginRegister;
+            Msg::RequestPasswordResetDone(Ok(_)) => {
+                self.is_password_reset_loading = false;
+                self.password_reset_message =
+                    Some("If that account exists, you'll receive reset instructions".into());
                 true
             }
-            Msg::LogoutDone(Err(_)) => {
-                self.is_logout_disabled = false;
-                self.is_logout_loading = false;
-                self.logout_error = Some("Could not logout".into());
+            Msg::RequestPasswordResetDone(Err(_)) => {
+                self.is_password_reset_loading = false;
+                self.password_reset_error = Some("Could not request a password reset".into());
                 true
             }
-            Msg::FetchProfiles => {
+            Msg::GuestLogin => {
+                self.claim_fetch_slot("GuestLogin");
+
+                self.loginregister_error = None;
+                self.logout_warning = None;
+                self.is_register_disabled = true;
+                self.is_login_disabled = true;
+                self.is_guest_login_loading = true;
+
                 if let Some(config) = &self.config {
                     self.ft = Some(
                         self.fetch_service.fetch(
-                            Request::builder()
-                                .method("GET")
-                                .uri(&format!("{}/v1/profiles", config.api_url))
-                                .header(
-                                    "Authorization",
-                                    self.state.token.as_ref().unwrap().to_owned(),
-                                )
-                                .body(Nothing)
-                                .unwrap(),
+                            request_builder(
+                                config,
+                                "POST",
+                                &format!("{}/v1/auth/guest", config.api_url),
+                                &self.request_id,
+                            )
+                            .header("Content-Type", "application/json")
+                            .body(Nothing)
+                            .unwrap(),
                             self.link.send_back(
-                                move |response: Response<Json<Result<ProfilesResponse, Error>>>| {
-                                    let (meta, Json(profiles)) = response.into_parts();
+                                move |response: Response<
+                                    Json<Result<GuestLoginResponse, Error>>,
+                                >| {
+                                    let (meta, Json(data)) = response.into_parts();
                                     if meta.status.is_success() {
-                                        Msg::FetchProfilesDone(profiles)
+                                        Msg::GuestLoginDone(data)
                                     } else {
-                                        Msg::FetchProfilesDone(Err(format_err!(
-                                            "{}: could not fetch profiles",
+                                        Msg::GuestLoginDone(Err(format_err!(
+                                            "{}: could not login as guest",
                                             meta.status
                                         )))
                                     }
@@ -425,161 +1450,3329 @@ impl Component for Model {
                 };
                 true
             }
-            Msg::FetchProfilesDone(Err(_)) => {
-                self.fetch_profiles_error = Some("Could not fetch profiles".into());
+            Msg::GuestLoginDone(Ok(guest_login_response)) => {
+                let token = match guest_login_response.token {
+                    Some(token) => token,
+                    None => {
+                        self.link.send_self(Msg::GuestLoginDone(Err(format_err!(
+                            "guest login response has no token"
+                        ))));
+                        return false;
+                    }
+                };
+
+                self.is_register_disabled = false;
+                self.is_login_disabled = false;
+                self.is_guest_login_loading = false;
+                self.is_guest = true;
+                // Guest tokens are deliberately kept in memory only.
+                self.state.token = Some(token);
+                self.audit("Logged in as guest".into());
+                self.link.send_self(Msg::FetchProfiles);
                 true
             }
-            Msg::FetchProfilesDone(Ok(profiles_response)) => {
-                self.scene = Scene::LoggedIn;
-
-                self.enabled_profiles.clear();
-                for profile in profiles_response.profiles.iter() {
-                    self.enabled_profiles
-                        .insert(profile.machine_name.to_owned());
+            Msg::GuestLoginDone(Err(_)) => {
+                self.is_register_disabled = false;
+                self.is_login_disabled = false;
+                self.is_guest_login_loading = false;
+                self.loginregister_error = Some("Could not continue as guest".into());
+                true
+            }
+            Msg::ToggleRememberUsername => {
+                self.settings.remember_username = !self.settings.remember_username;
+                if !self.settings.remember_username {
+                    self.settings.remembered_username = None;
                 }
-
-                self.fetched_profiles = Some(profiles_response);
+                self.persist_settings();
                 true
             }
-            Msg::ToggleProfile(machine_name) => {
-                if self.enabled_profiles.contains(&machine_name) {
-                    self.enabled_profiles.remove(&machine_name);
-                } else {
-                    self.enabled_profiles.insert(machine_name.to_owned());
+            Msg::LoginRegisterFormDataChange(field, value) => {
+                match field {
+                    LoginRegisterFormDataField::Username => {
+                        self.loginregister_form.username = value;
+                    }
+                    LoginRegisterFormDataField::Password => {
+                        self.loginregister_form.password = value;
+                    }
                 }
 
-                false
+                // Errors only describe the last submission, so they go stale once edited.
+                self.loginregister_error.take().is_some()
             }
-            Msg::LoadFile(ChangeData::Files(ref file_list)) if file_list.len() == 1 => {
-                let file = file_list.into_iter().next().unwrap();
-                self.is_file_uploading = true;
+            Msg::ConfirmLogout => {
+                let offers_everywhere = self.feature_enabled("logout_all") && !self.is_guest;
+                let offers_audit_log = !self.audit_log.is_empty();
+                let is_scanning = self.is_polling();
 
-                self.rt = Some(
-                    self.reader_service
-                        .read_file(file, self.link.send_back(Msg::CreateReport)),
-                );
+                if !offers_everywhere && !offers_audit_log && !is_scanning {
+                    self.link.send_self(Msg::Logout);
+                    return false;
+                }
+
+                let mut body = Vec::new();
+                let mut actions = Vec::new();
+
+                // Logging out stops polling, and the scan's results are not shown again.
+                if is_scanning {
+                    body.push("A scan is still running — log out anyway?");
+                }
+
+                if offers_audit_log {
+                    self.audit("Logout requested".into());
+                    body.push("Your session log is cleared when you log out.");
+                    actions.push(ModalAction {
+                        label: "Download session log",
+                        class: "button",
+                        msg: || Msg::DownloadAuditLog,
+                    });
+                }
 
+                actions.push(ModalAction {
+                    label: if is_scanning && !offers_everywhere {
+                        "Log out anyway"
+                    } else {
+                        "Log out here"
+                    },
+                    class: "button is-info",
+                    msg: || Msg::Logout,
+                });
+
+                if offers_everywhere {
+                    body.push(
+                        "Log out of this browser only, or end your sessions on every device?",
+                    );
+                    actions.push(ModalAction {
+                        label: "Log out everywhere",
+                        class: "button is-danger",
+                        msg: || Msg::LogoutEverywhere,
+                    });
+                }
+
+                actions.push(ModalAction {
+                    label: "Cancel",
+                    class: "button",
+                    msg: || Msg::DismissModal,
+                });
+
+                self.modal = Some(Modal {
+                    title: "Logout".into(),
+                    body: body.join("\n"),
+                    actions,
+                });
                 true
             }
-            Msg::CreateReport(file_data) => {
+            Msg::DownloadAuditLog => {
+                download_text(
+                    "session-log.csv".into(),
+                    "text/csv",
+                    audit_log_to_csv(&self.audit_log),
+                );
+                false
+            }
+            Msg::Logout => {
+                self.claim_fetch_slot("Logout");
+
+                self.modal = None;
+
                 if let Some(config) = &self.config {
+                    self.logout_everywhere_error = None;
+                    self.is_logout_disabled = true;
+                    self.is_logout_loading = true;
+
+                    let uri = format!("{}/v1/auth/logout", config.api_url);
+                    self.last_request = Some(RecordedRequest::new(
+                        "POST",
+                        &uri,
+                        &self.state.token,
+                        RequestBody::Empty,
+                    ));
+
                     self.ft = Some(
-                        self.fetch_service.fetch_binary(
-                            Request::builder()
-                                .method("POST")
-                                .uri(&format!(
-                                    "{}/v1/reports/create?profiles={}",
-                                    config.api_url,
-                                    self.enabled_profiles
-                                        .clone()
-                                        .into_iter()
-                                        .collect::<Vec<String>>()
-                                        .join(",")
-                                ))
+                        self.fetch_service.fetch(
+                            request_builder(config, "POST", &uri, &self.request_id)
+                                .header("Content-Type", "application/json")
                                 .header(
                                     "Authorization",
                                     self.state.token.as_ref().unwrap().to_owned(),
                                 )
-                                .body(Ok(file_data.content))
+                                .body(Nothing)
                                 .unwrap(),
-                            self.link.send_back(
-                                move |response: Response<Json<Result<CreateResponse, Error>>>| {
-                                    let (meta, Json(response)) = response.into_parts();
-                                    if meta.status.is_success() {
-                                        Msg::CreateReportDone(response)
-                                    } else {
-                                        Msg::CreateReportDone(Err(format_err!(
-                                            "{}: could not create report",
-                                            meta.status
-                                        )))
-                                    }
-                                },
-                            ),
+                            self.link.send_back(move |response: Response<Text>| {
+                                let (meta, body) = response.into_parts();
+                                Msg::LogoutDone(parse_logout_response(meta.status, body))
+                            }),
                         ),
                     );
+                    self.logout_tt = Some(self.timeout_service.spawn(
+                        std::time::Duration::from_millis(LOGOUT_TIMEOUT_MS),
+                        self.link.send_back(|_| Msg::LogoutTimedOut),
+                    ));
                 };
+                true
+            }
+            Msg::LogoutDone(Ok(_)) => {
+                self.logout_tt = None;
+                self.is_logout_disabled = false;
+                self.is_logout_loading = false;
+                self.clear_session();
+                self.loginregister_error = None;
+                true
+            }
+            Msg::LogoutDone(Err(error)) => {
+                // The local session goes away regardless, so nobody gets stuck logged in.
+                self.logout_tt = None;
+                self.is_logout_disabled = false;
+                self.is_logout_loading = false;
+                self.clear_session();
+                self.loginregister_error = None;
+                self.logout_warning = Some(format!(
+                    "Logged out here, but the server reported an error: {}",
+                    error
+                ));
+                true
+            }
+            Msg::LogoutTimedOut => {
+                self.logout_tt = None;
+                if !self.is_logout_loading {
+                    return false;
+                }
 
-                false
+                // Dropping the request cancels it, so a late answer cannot log out a new session.
+                self.ft = None;
+                self.is_logout_disabled = false;
+                self.is_logout_loading = false;
+                self.clear_session();
+                self.loginregister_error = None;
+                self.logout_warning =
+                    Some("Logged out here, but the server did not confirm the logout".into());
+                true
             }
-            Msg::CreateReportDone(Ok(create_response)) => {
-                self.is_file_uploading = false;
+            Msg::LogoutEverywhere => {
+                self.claim_fetch_slot("LogoutEverywhere");
 
-                self.link
-                    .send_self(Msg::FetchTasks(create_response.report_id));
-                self.it = Some(
-                    self.interval_service.spawn(
-                        std::time::Duration::from_millis(1000),
-                        self.link
-                            .send_back(move |_| Msg::FetchTasks(create_response.report_id)),
-                    ),
-                );
+                self.modal = None;
+
+                if let Some(config) = &self.config {
+                    self.logout_everywhere_error = None;
+                    self.is_logout_disabled = true;
+                    self.is_logout_everywhere_loading = true;
 
+                    let uri = format!("{}/v1/auth/logout-all", config.api_url);
+                    self.last_request = Some(RecordedRequest::new(
+                        "POST",
+                        &uri,
+                        &self.state.token,
+                        RequestBody::Empty,
+                    ));
+
+                    self.ft = Some(
+                        self.fetch_service.fetch(
+                            request_builder(config, "POST", &uri, &self.request_id)
+                                .header("Content-Type", "application/json")
+                                .header(
+                                    "Authorization",
+                                    self.state.token.as_ref().unwrap().to_owned(),
+                                )
+                                .body(Nothing)
+                                .unwrap(),
+                            self.link.send_back(move |response: Response<Nothing>| {
+                                let (meta, _) = response.into_parts();
+                                if meta.status.is_success() {
+                                    Msg::LogoutEverywhereDone(Ok(()))
+                                } else {
+                                    Msg::LogoutEverywhereDone(Err(format_err!(
+                                        "{}: could not logout everywhere",
+                                        meta.status
+                                    )))
+                                }
+                            }),
+                        ),
+                    );
+                };
                 true
             }
-            Msg::CreateReportDone(Err(_)) => {
-                self.is_file_uploading = false;
-
+            Msg::LogoutEverywhereDone(Ok(_)) => {
+                self.is_logout_disabled = false;
+                self.is_logout_everywhere_loading = false;
+                self.clear_session();
+                self.loginregister_error = None;
                 true
             }
-            Msg::FetchTasks(report_id) => {
+            Msg::LogoutEverywhereDone(Err(_)) => {
+                self.is_logout_disabled = false;
+                self.is_logout_everywhere_loading = false;
+                self.logout_everywhere_error = Some("Could not log out everywhere".into());
+                true
+            }
+            Msg::FetchProfiles => {
+                self.claim_fetch_slot("FetchProfiles");
+
+                self.fetch_profiles_error = None;
+
                 if let Some(config) = &self.config {
+                    let uri = config.profiles_uri();
+                    self.last_request = Some(RecordedRequest::new(
+                        "GET",
+                        &uri,
+                        &self.state.token,
+                        RequestBody::Empty,
+                    ));
+
                     self.ft = Some(
                         self.fetch_service.fetch(
-                            Request::builder()
-                                .method("GET")
-                                .uri(&format!(
-                                    "{}/v1/reports/{}/tasks",
-                                    config.api_url, report_id
-                                ))
+                            request_builder(config, "GET", &uri, &self.request_id)
                                 .header(
                                     "Authorization",
                                     self.state.token.as_ref().unwrap().to_owned(),
                                 )
                                 .body(Nothing)
                                 .unwrap(),
-                            self.link.send_back(
-                                move |response: Response<Json<Result<TasksResponse, Error>>>| {
-                                    let (meta, Json(response)) = response.into_parts();
-                                    if meta.status.is_success() {
-                                        Msg::FetchTasksDone(response)
-                                    } else {
-                                        Msg::FetchTasksDone(Err(format_err!(
-                                            "{}: could not fetch tasks",
-                                            meta.status
-                                        )))
-                                    }
-                                },
-                            ),
+                            self.link.send_back(move |response: Response<Text>| {
+                                let (meta, body) = response.into_parts();
+                                let maintenance = body
+                                    .as_ref()
+                                    .ok()
+                                    .and_then(|x| parse_maintenance_response(meta.status, x));
+
+                                if let Some(maintenance) = maintenance {
+                                    Msg::MaintenanceStarted(maintenance.message)
+                                } else if meta.status.is_success() {
+                                    Msg::FetchProfilesDone(
+                                        body.and_then(|x| parse_profiles_response(&x)),
+                                    )
+                                } else if meta.status == StatusCode::UNAUTHORIZED {
+                                    Msg::SessionExpired
+                                } else if meta.status.is_server_error() {
+                                    Msg::TransientFailure(RetryTarget::Profiles)
+                                } else {
+                                    Msg::FetchProfilesDone(Err(format_err!(
+                                        "{}: could not fetch profiles",
+                                        meta.status
+                                    )))
+                                }
+                            }),
                         ),
                     );
                 };
+                true
+            }
+            Msg::FetchProfilesDone(Err(error)) => {
+                let is_decode_error = error.downcast_ref::<serde_json::Error>().is_some();
+                let is_debug = self.config.as_ref().is_some_and(|x| x.debug);
+
+                self.fetch_profiles_error = Some(match (is_decode_error, is_debug) {
+                    (true, true) => format!("Unexpected response from server: {}", error),
+                    (true, false) => "Unexpected response from server".into(),
+                    (false, _) => "Could not fetch profiles".into(),
+                });
+                true
+            }
+            Msg::FetchProfilesDone(Ok(profiles_response)) => {
+                // A late answer after logout must not bring the session back.
+                if !self.transition(Scene::LoggedIn) {
+                    return false;
+                }
+                self.mark_reconnected();
+                self.start_idle_tracking();
+                self.start_profiles_refresh();
+                if self.session_restored && self.fetched_profiles.is_none() {
+                    self.welcome_back_tt = Some(self.timeout_service.spawn(
+                        std::time::Duration::from_millis(WELCOME_BACK_MS),
+                        self.link.send_back(|_| Msg::HideWelcomeBack),
+                    ));
+                }
+                if self.upload_limits.is_none() && self.limits_ft.is_none() {
+                    self.link.send_self(Msg::FetchLimits);
+                }
+
+                self.enabled_profiles = reconcile_enabled_profiles(
+                    &profiles_response.profiles,
+                    self.settings.enabled_profile_ids.as_ref(),
+                    self.settings.known_profile_ids.as_ref(),
+                    self.settings.auto_enable_new_profiles,
+                );
+                self.settings.muted_profile_ids.retain(|x| {
+                    profiles_response
+                        .profiles
+                        .iter()
+                        .any(|profile| profile.id == *x)
+                });
+                for profile_id in &self.settings.muted_profile_ids {
+                    self.enabled_profiles.remove(profile_id);
+                }
+
+                let duplicates = duplicate_machine_names(&profiles_response.profiles);
+                if !duplicates.is_empty() {
+                    self.console_service.warn(&format!(
+                        "Profiles share machine names: {}",
+                        duplicates.join(", ")
+                    ));
+                }
+
+                self.settings.known_profile_ids =
+                    Some(profiles_response.profiles.iter().map(|x| x.id).collect());
+                self.settings.last_results = reconcile_last_results(
+                    &profiles_response.profiles,
+                    &self.settings.last_results,
+                );
+                self.fetched_profiles = Some(profiles_response);
+
+                if self.settings.enabled_profile_ids.is_some() {
+                    self.persist_enabled_profiles();
+                } else {
+                    self.persist_settings();
+                }
+
+                if let Some(report_id) = self.state.report_id {
+                    if !self.is_polling() {
+                        self.link.send_self(Msg::FetchTasks(report_id));
+                        self.start_polling(report_id);
+                    }
+                }
+                true
+            }
+            Msg::ToggleProfile(profile_id) => {
+                if self.settings.muted_profile_ids.contains(&profile_id) {
+                    return false;
+                }
+
+                if !self.enabled_profiles.remove(&profile_id) {
+                    self.enabled_profiles.insert(profile_id);
+                }
+
+                if self.large_selection_warning().is_none() {
+                    self.is_large_selection_acknowledged = false;
+                }
+
+                self.persist_enabled_profiles();
 
+                true
+            }
+            Msg::SelectAllProfiles => {
+                if let Some(profiles_response) = &self.fetched_profiles {
+                    self.enabled_profiles = profiles_response
+                        .profiles
+                        .iter()
+                        .map(|x| x.id)
+                        .filter(|x| !self.settings.muted_profile_ids.contains(x))
+                        .collect();
+                }
+
+                self.persist_enabled_profiles();
+
+                true
+            }
+            Msg::ToggleAutoEnableNewProfiles => {
+                self.settings.auto_enable_new_profiles = !self.settings.auto_enable_new_profiles;
+                self.persist_settings();
+                true
+            }
+            Msg::ToggleCompletionNotifications => {
+                self.settings.notify_on_completion = !self.settings.notify_on_completion;
+                if self.settings.notify_on_completion {
+                    request_notification_permission();
+                }
+                self.persist_settings();
+                true
+            }
+            Msg::DragProfileStart(profile_id) => {
+                self.dragged_profile_id = Some(profile_id);
                 false
             }
-            Msg::FetchTasksDone(Ok(fetch_response)) => {
-                let pending_tasks: Vec<&Task> = fetch_response
-                    .tasks
-                    .iter()
-                    .filter(|x| x.status == "new" || x.status == "pending")
-                    .collect();
+            Msg::DropProfile(target_id) => {
+                let dragged_id = match self.dragged_profile_id.take() {
+                    Some(dragged_id) if dragged_id != target_id => dragged_id,
+                    _ => return false,
+                };
 
-                if pending_tasks.len() == 0 {
-                    self.it = None;
+                if let Some(profiles_response) = &self.fetched_profiles {
+                    let mut order: Vec<i64> =
+                        order_profiles(&profiles_response.profiles, &self.settings.profile_order)
+                            .iter()
+                            .map(|x| x.id)
+                            .filter(|x| *x != dragged_id)
+                            .collect();
+
+                    if let Some(index) = order.iter().position(|x| *x == target_id) {
+                        order.insert(index, dragged_id);
+                    }
+
+                    self.settings.profile_order = order;
+                    self.persist_settings();
+                }
+                true
+            }
+            Msg::ToggleProfileConfig(profile_id) => {
+                if !self.expanded_profile_configs.remove(&profile_id) {
+                    self.expanded_profile_configs.insert(profile_id);
                 }
+                self.full_profile_configs.remove(&profile_id);
+                true
+            }
+            Msg::ShowFullProfileConfig(profile_id) => {
+                self.full_profile_configs.insert(profile_id);
+                true
+            }
+            Msg::ConfigOverrideChange(machine_name, value) => {
+                self.config_overrides.remove(&machine_name);
+                self.config_override_errors.remove(&machine_name);
 
-                self.current_pending_tasks = Some(fetch_response.tasks);
+                if !value.trim().is_empty() {
+                    match serde_json::from_str(&value) {
+                        Ok(config) => {
+                            self.config_overrides
+                                .insert(machine_name.to_owned(), config);
+                        }
+                        Err(error) => {
+                            self.config_override_errors
+                                .insert(machine_name.to_owned(), error.to_string());
+                        }
+                    }
+                }
 
+                self.config_override_drafts.insert(machine_name, value);
                 true
             }
-            Msg::FetchTasksDone(Err(_)) => true,
-            Msg::NoOp => false,
-            _ => false,
-        }
-    }
-}
+            Msg::CopyProfileConfig(profile_id) => {
+                let config = self
+                    .fetched_profiles
+                    .iter()
+                    .flat_map(|x| x.profiles.iter())
+                    .find(|x| x.id == profile_id)
+                    .and_then(|x| x.config.as_ref());
 
-impl Renderable<Model> for Model {
-    fn view(&self) -> Html<Self> {
+                if let Some(config) = config {
+                    copy_to_clipboard(config.to_string());
+                }
+                false
+            }
+            Msg::AcknowledgeLargeSelection => {
+                self.is_large_selection_acknowledged = true;
+                true
+            }
+            Msg::ToggleDetectionsFilter => {
+                self.show_detections_only = !self.show_detections_only;
+                true
+            }
+            Msg::ToggleCollapseClean => {
+                self.settings.collapse_clean = !self.settings.collapse_clean;
+                self.is_clean_expanded = false;
+                self.persist_settings();
+                true
+            }
+            Msg::ToggleCleanExpanded => {
+                self.is_clean_expanded = !self.is_clean_expanded;
+                true
+            }
+            Msg::ToggleGroupBySeverity => {
+                self.group_by_severity = !self.group_by_severity;
+                true
+            }
+            Msg::ToggleShowMutedProfiles => {
+                self.show_muted_profiles = !self.show_muted_profiles;
+                true
+            }
+            Msg::ToggleProfileMute(profile_id) => {
+                if !self.settings.muted_profile_ids.remove(&profile_id) {
+                    self.settings.muted_profile_ids.insert(profile_id);
+                    self.enabled_profiles.remove(&profile_id);
+                }
+
+                // Also stores the mute, along with the selection it changed.
+                if self.settings.enabled_profile_ids.is_some() {
+                    self.persist_enabled_profiles();
+                } else {
+                    self.persist_settings();
+                }
+                true
+            }
+            Msg::TogglePreviousReport => {
+                self.is_viewing_previous_report =
+                    !self.is_viewing_previous_report && self.previous_report.is_some();
+                true
+            }
+            Msg::SelectReportTab(report_id) => {
+                if self.current_report_id == Some(report_id) {
+                    return false;
+                }
+
+                let is_running = self
+                    .session_reports
+                    .iter()
+                    .any(|x| x.report_id == report_id && x.summary.is_none());
+
+                // A poll still in flight belongs to the report being left.
+                if self.is_polling_fetch_in_flight {
+                    self.ft = None;
+                }
+                self.stash_current_report();
+                self.stop_polling();
+
+                if is_running {
+                    self.state.report_id = Some(report_id);
+                    self.persist_state();
+                    self.link.send_self(Msg::FetchTasks(report_id));
+                    self.start_polling(report_id);
+                } else {
+                    self.state.report_id = None;
+                    self.persist_state();
+                    self.current_report_id = Some(report_id);
+                    self.current_report_hash = None;
+                    self.current_report_created_when = None;
+                    self.previous_verdicts = None;
+                    self.timed_out_task_ids.clear();
+                    self.is_viewing_previous_report = false;
+                    self.current_pending_tasks = self.report_tasks.remove(&report_id);
+                    self.update_background_polling();
+                }
+                true
+            }
+            Msg::PollBackgroundReports => {
+                let report_ids = self.background_report_ids();
+                if report_ids.is_empty() {
+                    self.background_it = None;
+                    return false;
+                }
+
+                self.begin_action("PollBackgroundReports");
+                if let Some(config) = &self.config {
+                    for report_id in report_ids {
+                        if self
+                            .background_fts
+                            .get(&report_id)
+                            .is_some_and(yew::services::Task::is_active)
+                        {
+                            continue;
+                        }
+
+                        let uri = config.tasks_uri(report_id);
+                        let task = self.fetch_service.fetch(
+                            request_builder(config, "GET", &uri, &self.request_id)
+                                .header(
+                                    "Authorization",
+                                    self.state.token.as_ref().unwrap().to_owned(),
+                                )
+                                .body(Nothing)
+                                .unwrap(),
+                            self.link.send_back(move |response: Response<Text>| {
+                                let (meta, body) = response.into_parts();
+                                if meta.status.is_success() {
+                                    Msg::FetchBackgroundTasksDone(
+                                        report_id,
+                                        body.and_then(|x| parse_tasks_response(&x)),
+                                    )
+                                } else if meta.status == StatusCode::UNAUTHORIZED {
+                                    Msg::SessionExpired
+                                } else {
+                                    Msg::FetchBackgroundTasksDone(
+                                        report_id,
+                                        Err(format_err!("{}: could not fetch tasks", meta.status)),
+                                    )
+                                }
+                            }),
+                        );
+                        self.background_fts.insert(report_id, task);
+                    }
+                }
+                false
+            }
+            Msg::FetchBackgroundTasksDone(report_id, Ok(fetch_response)) => {
+                self.background_fts.remove(&report_id);
+
+                // The report was brought to the front meanwhile and is polled there.
+                if self.current_report_id == Some(report_id) {
+                    return false;
+                }
+
+                let is_done = fetch_response
+                    .tasks
+                    .iter()
+                    .all(|x| x.status != "new" && x.status != "pending");
+
+                if is_done {
+                    let summary = summarize_tasks(&fetch_response.tasks);
+                    self.audit(format!("Report #{} completed: {}", report_id, summary));
+                    self.notify_scan_complete(report_id, &summary);
+                    if let Some(session_report) = self
+                        .session_reports
+                        .iter_mut()
+                        .find(|x| x.report_id == report_id)
+                    {
+                        session_report.summary = Some(summary);
+                    }
+                }
+
+                self.report_tasks.insert(report_id, fetch_response.tasks);
+                self.update_background_polling();
+                true
+            }
+            Msg::FetchBackgroundTasksDone(report_id, Err(_)) => {
+                // Retried at the next tick.
+                self.background_fts.remove(&report_id);
+                false
+            }
+            Msg::LoadFile(ChangeData::Files(ref file_list)) if file_list.len() == 1 => {
+                let file = file_list.into_iter().next().unwrap();
+                self.read_file(file);
+                true
+            }
+            Msg::PasteFile(file) => {
+                if let Scene::LoggedIn = self.scene {
+                    if !self.is_file_uploading {
+                        self.read_file(file);
+                        return true;
+                    }
+                }
+                false
+            }
+            Msg::ReadFileFailed => {
+                // Aborting a read also ends it without a result.
+                if !self.is_file_uploading {
+                    return false;
+                }
+
+                self.rt = None;
+                self.is_file_uploading = false;
+                self.create_report_error = Some("Could not read the selected file".into());
+                true
+            }
+            Msg::CancelFileRead => {
+                if let Some(rt) = self.rt.take() {
+                    rt.abort();
+                }
+                self.is_file_uploading = false;
+                true
+            }
+            Msg::CreateReport(file_data) => {
+                if !self.is_file_uploading {
+                    return false;
+                }
+
+                self.claim_fetch_slot("CreateReport");
+
+                self.is_report_creating = true;
+                self.create_report_rejection = None;
+                self.pending_file_name = Some(file_data.name);
+                // Large files are not kept around just in case a bundle is downloaded.
+                self.pending_file = Some(&file_data.content)
+                    .filter(|x| {
+                        self.feature_enabled("bundle_export") && x.len() <= MAX_BUNDLE_FILE_BYTES
+                    })
+                    .cloned();
+
+                if let Some(config) = &self.config {
+                    let (uri, mut builder) = self.create_report_request(config, InputKind::File);
+                    builder.header(
+                        "Authorization",
+                        self.state.token.as_ref().unwrap().to_owned(),
+                    );
+
+                    // Overrides do not fit the raw file body, so they switch it to JSON.
+                    let overrides = self.enabled_config_overrides();
+                    let (body_kind, body) = if overrides.is_empty() {
+                        (RequestBody::Binary, Ok(file_data.content))
+                    } else {
+                        builder.header("Content-Type", "application/json");
+                        let body = serde_json::to_vec(&CreateFileReportRequest {
+                            file: encode_base64(&file_data.content),
+                            overrides,
+                        });
+                        (RequestBody::Json, body.map_err(Error::from))
+                    };
+                    self.last_request = Some(RecordedRequest::new(
+                        "POST",
+                        &uri,
+                        &self.state.token,
+                        body_kind,
+                    ));
+
+                    self.ft = Some(self.fetch_service.fetch_binary(
+                        builder.body(body).unwrap(),
+                        self.link.send_back(move |response: Response<Binary>| {
+                            let (meta, body) = response.into_parts();
+                            Msg::CreateReportDone(parse_create_report_response(
+                                meta.status,
+                                body.and_then(|x| Ok(String::from_utf8(x)?)),
+                            ))
+                        }),
+                    ));
+                };
+
+                false
+            }
+            Msg::CreateReportDone(Ok(create_response)) => {
+                self.is_file_uploading = false;
+                self.is_url_submitting = false;
+                self.is_report_creating = false;
+                self.is_report_unavailable = false;
+
+                self.state.report_id = Some(create_response.report_id);
+                self.persist_state();
+
+                let file_name = self.pending_file_name.take().unwrap_or_default();
+                if let Some(file) = self.pending_file.take() {
+                    self.bundle_file = Some((create_response.report_id, file));
+                }
+                self.audit(format!(
+                    "Uploaded {} as report #{}",
+                    file_name, create_response.report_id
+                ));
+                self.session_reports.push(SessionReport {
+                    report_id: create_response.report_id,
+                    file_name,
+                    summary: None,
+                });
+
+                self.link
+                    .send_self(Msg::FetchTasks(create_response.report_id));
+                self.start_polling(create_response.report_id);
+                self.fetch_report(create_response.report_id);
+
+                true
+            }
+            Msg::CreateReportDone(Err(error)) => {
+                self.is_file_uploading = false;
+                self.is_url_submitting = false;
+                self.is_report_creating = false;
+                self.pending_file = None;
+
+                self.create_report_rejection = error.downcast_ref().cloned();
+                if self.create_report_rejection.is_none() {
+                    self.create_report_error = Some("Could not create report".into());
+                }
+
+                true
+            }
+            Msg::LookupHashChange(value) => {
+                self.lookup_hash = value;
+                false
+            }
+            Msg::LookupHash => {
+                if self.is_lookup_loading {
+                    return false;
+                }
+                let hash = self.lookup_hash.trim().to_lowercase();
+
+                if !is_valid_multihash(&hash) {
+                    self.lookup_reports = None;
+                    self.lookup_error = Some("Please enter a valid hex-encoded multihash".into());
+                    return true;
+                }
+
+                self.begin_action("LookupHash");
+                if let Some(config) = &self.config {
+                    self.lookup_error = None;
+                    self.is_lookup_loading = true;
+
+                    let uri = format!("{}/v1/reports?hash={}", config.api_url, hash);
+                    self.last_request = Some(RecordedRequest::new(
+                        "GET",
+                        &uri,
+                        &self.state.token,
+                        RequestBody::Empty,
+                    ));
+
+                    self.lookup_ft = Some(
+                        self.fetch_service.fetch(
+                            request_builder(config, "GET", &uri, &self.request_id)
+                                .header(
+                                    "Authorization",
+                                    self.state.token.as_ref().unwrap().to_owned(),
+                                )
+                                .body(Nothing)
+                                .unwrap(),
+                            self.link.send_back(
+                                move |response: Response<Json<Result<ReportsResponse, Error>>>| {
+                                    let (meta, Json(response)) = response.into_parts();
+                                    if meta.status.is_success() {
+                                        Msg::LookupHashDone(response)
+                                    } else if meta.status == StatusCode::UNAUTHORIZED {
+                                        Msg::SessionExpired
+                                    } else {
+                                        Msg::LookupHashDone(Err(format_err!(
+                                            "{}: could not look up hash",
+                                            meta.status
+                                        )))
+                                    }
+                                },
+                            ),
+                        ),
+                    );
+                };
+
+                true
+            }
+            Msg::LookupHashDone(Ok(reports_response)) => {
+                self.lookup_ft = None;
+                self.is_lookup_loading = false;
+                self.lookup_reports = Some(reports_response.reports);
+                true
+            }
+            Msg::LookupHashDone(Err(_)) => {
+                self.lookup_ft = None;
+                self.is_lookup_loading = false;
+                self.lookup_reports = None;
+                self.lookup_error = Some("Could not look up this hash".into());
+                true
+            }
+            Msg::ScanUrlChange(value) => {
+                let input_kind = if value.trim().is_empty() {
+                    InputKind::File
+                } else {
+                    InputKind::Url
+                };
+                self.scan_url = value;
+
+                let is_changed = input_kind != self.scan_input_kind;
+                self.scan_input_kind = input_kind;
+                is_changed
+            }
+            Msg::ScanUrl => {
+                let url = self.scan_url.trim().to_owned();
+
+                if !is_valid_scan_url(&url) {
+                    self.create_report_error = Some("Please enter a valid http(s) URL".into());
+                    return true;
+                }
+
+                if !self.config_override_errors.is_empty() {
+                    self.create_report_error =
+                        Some("Fix the invalid config overrides first".into());
+                    return true;
+                }
+
+                self.claim_fetch_slot("ScanUrl");
+
+                if let Some(config) = &self.config {
+                    self.create_report_error = None;
+                    self.create_report_rejection = None;
+                    self.is_url_submitting = true;
+                    self.is_report_creating = true;
+                    self.pending_file_name = Some(url.clone());
+                    self.pending_file = None;
+
+                    let (uri, mut builder) = self.create_report_request(config, InputKind::Url);
+                    self.last_request = Some(RecordedRequest::new(
+                        "POST",
+                        &uri,
+                        &self.state.token,
+                        RequestBody::Json,
+                    ));
+
+                    self.ft = Some(
+                        self.fetch_service.fetch(
+                            builder
+                                .header("Content-Type", "application/json")
+                                .header(
+                                    "Authorization",
+                                    self.state.token.as_ref().unwrap().to_owned(),
+                                )
+                                .body(Json(&CreateUrlReportRequest {
+                                    url,
+                                    overrides: self.enabled_config_overrides(),
+                                }))
+                                .unwrap(),
+                            self.link.send_back(move |response: Response<Text>| {
+                                let (meta, body) = response.into_parts();
+                                Msg::CreateReportDone(parse_create_report_response(
+                                    meta.status,
+                                    body,
+                                ))
+                            }),
+                        ),
+                    );
+                };
+
+                true
+            }
+            Msg::ViewportChanged(is_mobile) => {
+                self.is_mobile = is_mobile;
+                true
+            }
+            Msg::VisibilityChanged(true) => {
+                let is_pause_enabled = self
+                    .config
+                    .as_ref()
+                    .is_some_and(|x| x.pause_polling_when_hidden.unwrap_or(true));
+
+                if is_pause_enabled && self.it.is_some() {
+                    self.it = None;
+                    self.paused_report_id = self.current_report_id;
+                }
+                false
+            }
+            Msg::UserActivity => {
+                // Once warned, only an explicit snooze keeps the session.
+                if !self.is_idle_warning_shown {
+                    self.extend_idle_deadline(self.idle_logout_duration());
+                }
+                false
+            }
+            Msg::AutoRefreshProfiles => {
+                // Skipped rather than queued; the next tick tries again.
+                let is_busy = self.is_polling()
+                    || self.is_file_uploading
+                    || self.is_report_creating
+                    || self.dragged_profile_id.is_some()
+                    || self.modal.is_some()
+                    || self.is_fetch_slot_busy();
+                if self.scene != Scene::LoggedIn || is_busy {
+                    return false;
+                }
+
+                self.link.send_self(Msg::FetchProfiles);
+                false
+            }
+            Msg::IdleTick => {
+                let remaining = match self.idle_deadline {
+                    Some(deadline) => deadline.signed_duration_since(Utc::now()),
+                    None => return false,
+                };
+
+                if remaining <= chrono::Duration::zero() {
+                    self.audit("Logged out after inactivity".into());
+                    self.stop_idle_tracking();
+                    self.link.send_self(Msg::Logout);
+                    return true;
+                }
+
+                let was_shown = self.is_idle_warning_shown;
+                self.is_idle_warning_shown = remaining <= self.idle_warning_duration();
+                was_shown || self.is_idle_warning_shown
+            }
+            Msg::SnoozeIdleLogout => {
+                self.is_idle_warning_shown = false;
+                let snooze = self
+                    .config
+                    .as_ref()
+                    .and_then(|x| x.idle_snooze_minutes)
+                    .map(|x| chrono::Duration::minutes(x as i64));
+                self.extend_idle_deadline(snooze.or_else(|| self.idle_logout_duration()));
+                true
+            }
+            Msg::VisibilityChanged(false) => {
+                if let Some(report_id) = self.paused_report_id.take() {
+                    self.link.send_self(Msg::PollReport(report_id));
+                    self.spawn_poll_interval(report_id);
+                }
+                false
+            }
+            Msg::PollReport(report_id) => {
+                if self.is_status_rollup_unavailable {
+                    self.link.send_self(Msg::FetchTasks(report_id));
+                    return false;
+                }
+
+                // Polls yield to other requests, the next tick retries.
+                if self.is_fetch_slot_busy() {
+                    return false;
+                }
+                self.claim_fetch_slot("PollReport");
+
+                if let Some(config) = &self.config {
+                    self.is_polling_fetch_in_flight = true;
+
+                    let uri = format!("{}/v1/reports/{}/status", config.api_url, report_id);
+                    self.last_request = Some(RecordedRequest::new(
+                        "GET",
+                        &uri,
+                        &self.state.token,
+                        RequestBody::Empty,
+                    ));
+
+                    self.ft = Some(
+                        self.fetch_service.fetch(
+                            request_builder(config, "GET", &uri, &self.request_id)
+                                .header(
+                                    "Authorization",
+                                    self.state.token.as_ref().unwrap().to_owned(),
+                                )
+                                .body(Nothing)
+                                .unwrap(),
+                            self.link.send_back(
+                                move |response: Response<
+                                    Json<Result<ReportStatusResponse, Error>>,
+                                >| {
+                                    let (meta, Json(response)) = response.into_parts();
+                                    if meta.status.is_success() {
+                                        Msg::FetchReportStatusDone(
+                                            report_id,
+                                            response.map(|x| x.status),
+                                        )
+                                    } else if meta.status == StatusCode::UNAUTHORIZED {
+                                        Msg::SessionExpired
+                                    } else if meta.status.is_server_error() {
+                                        Msg::TransientFailure(RetryTarget::Poll)
+                                    } else {
+                                        Msg::FetchReportStatusDone(
+                                            report_id,
+                                            Err(format_err!(
+                                                "{}: could not fetch report status",
+                                                meta.status
+                                            )),
+                                        )
+                                    }
+                                },
+                            ),
+                        ),
+                    );
+                };
+
+                true
+            }
+            Msg::FetchReportStatusDone(report_id, Ok(status)) => {
+                self.mark_reconnected();
+                self.is_polling_fetch_in_flight = false;
+
+                if status == ReportStatus::Pending {
+                    self.poll_attempts += 1;
+
+                    // Fetch the tasks once more so the timeout can mark the stuck ones.
+                    if self.poll_attempts >= MAX_POLL_ATTEMPTS {
+                        self.link.send_self(Msg::FetchTasks(report_id));
+                    }
+                } else {
+                    self.link.send_self(Msg::FetchTasks(report_id));
+                }
+                true
+            }
+            Msg::FetchReportStatusDone(report_id, Err(_)) => {
+                // Older backends lack the rollup endpoint, poll the tasks directly instead.
+                self.is_status_rollup_unavailable = true;
+                self.link.send_self(Msg::FetchTasks(report_id));
+                true
+            }
+            Msg::FetchTasks(report_id) => {
+                // Polls yield to other requests, the next tick retries.
+                if self.is_fetch_slot_busy() {
+                    return false;
+                }
+                self.claim_fetch_slot("FetchTasks");
+
+                if let Some(config) = &self.config {
+                    self.is_polling_fetch_in_flight = true;
+                    self.tasks_fetch_seq += 1;
+                    let seq = self.tasks_fetch_seq;
+
+                    let uri = config.tasks_uri(report_id);
+                    self.last_request = Some(RecordedRequest::new(
+                        "GET",
+                        &uri,
+                        &self.state.token,
+                        RequestBody::Empty,
+                    ));
+
+                    self.ft = Some(
+                        self.fetch_service.fetch(
+                            request_builder(config, "GET", &uri, &self.request_id)
+                                .header(
+                                    "Authorization",
+                                    self.state.token.as_ref().unwrap().to_owned(),
+                                )
+                                .body(Nothing)
+                                .unwrap(),
+                            self.link.send_back(move |response: Response<Text>| {
+                                let (meta, body) = response.into_parts();
+                                let maintenance = body
+                                    .as_ref()
+                                    .ok()
+                                    .and_then(|x| parse_maintenance_response(meta.status, x));
+
+                                if let Some(maintenance) = maintenance {
+                                    Msg::MaintenanceStarted(maintenance.message)
+                                } else if meta.status.is_success() {
+                                    Msg::FetchTasksDone(
+                                        seq,
+                                        body.and_then(|x| parse_tasks_response(&x)),
+                                    )
+                                } else if meta.status == StatusCode::NOT_FOUND {
+                                    Msg::ReportNotFound
+                                } else if meta.status == StatusCode::UNAUTHORIZED {
+                                    Msg::SessionExpired
+                                } else if meta.status.is_server_error() {
+                                    Msg::TransientFailure(RetryTarget::Poll)
+                                } else {
+                                    Msg::FetchTasksDone(
+                                        seq,
+                                        Err(format_err!("{}: could not fetch tasks", meta.status)),
+                                    )
+                                }
+                            }),
+                        ),
+                    );
+                };
+
+                true
+            }
+            Msg::FetchTasksDone(seq, Ok(fetch_response)) => {
+                // A delayed answer must not take a finished task back to pending.
+                if seq <= self.applied_tasks_seq {
+                    return false;
+                }
+                self.applied_tasks_seq = seq;
+                if seq == self.tasks_fetch_seq {
+                    self.is_polling_fetch_in_flight = false;
+                }
+                self.mark_reconnected();
+
+                let verdicts = task_verdicts(&fetch_response.tasks);
+                if verdicts
+                    .iter()
+                    .any(|(id, status)| self.settings.last_results.get(id) != Some(status))
+                {
+                    self.settings.last_results.extend(verdicts);
+                    self.persist_settings();
+                }
+
+                let pending_tasks: Vec<&Task> = fetch_response
+                    .tasks
+                    .iter()
+                    .filter(|x| x.status == "new" || x.status == "pending")
+                    .collect();
+
+                if pending_tasks.len() == 0 {
+                    self.stop_polling();
+                    self.state.report_id = None;
+                    self.persist_state();
+
+                    let summary = summarize_tasks(&fetch_response.tasks);
+                    let report_id = self.current_report_id;
+                    if let Some(report_id) = report_id {
+                        self.audit(format!("Report #{} completed: {}", report_id, summary));
+                        self.notify_scan_complete(report_id, &summary);
+                    }
+                    if let Some(session_report) = self
+                        .session_reports
+                        .iter_mut()
+                        .find(|x| Some(x.report_id) == report_id)
+                    {
+                        session_report.summary = Some(summary);
+                    }
+
+                    if let Some(hash) = &self.current_report_hash {
+                        self.verdict_cache
+                            .insert(hash.to_owned(), task_verdicts(&fetch_response.tasks));
+                    }
+                }
+
+                if !pending_tasks.is_empty() {
+                    self.poll_attempts += 1;
+
+                    // Give up on tasks that never finish, but let the user resume.
+                    if self.poll_attempts >= MAX_POLL_ATTEMPTS {
+                        self.timed_out_task_ids = pending_tasks.iter().map(|x| x.id).collect();
+                        self.stop_polling();
+                    }
+                }
+
+                let pending_count = if self.is_polling() {
+                    pending_tasks.len()
+                } else {
+                    0
+                };
+                set_document_title(pending_count);
+
+                let announcement = self.describe_task_changes(&fetch_response.tasks);
+                if !announcement.is_empty() {
+                    self.scan_announcement = announcement;
+                }
+
+                self.current_pending_tasks = Some(fetch_response.tasks);
+
+                true
+            }
+            Msg::FetchTasksDone(seq, Err(_)) => {
+                if seq == self.tasks_fetch_seq {
+                    self.is_polling_fetch_in_flight = false;
+                }
+                true
+            }
+            Msg::FetchReportDone(Ok(report)) => {
+                self.report_ft = None;
+
+                if Some(report.id) == self.current_report_id {
+                    self.previous_verdicts =
+                        self.verdict_cache.get(&report.file_multihash).cloned();
+
+                    if self.is_scan_complete() {
+                        if let Some(tasks) = &self.current_pending_tasks {
+                            self.verdict_cache
+                                .insert(report.file_multihash.to_owned(), task_verdicts(tasks));
+                        }
+                    }
+
+                    self.current_report_hash = Some(report.file_multihash);
+                    self.current_report_created_when = Some(report.created_when);
+                }
+                true
+            }
+            Msg::FetchReportDone(Err(_)) => {
+                self.report_ft = None;
+                false
+            }
+            Msg::ReportNotFound => {
+                self.stop_polling();
+                self.state.report_id = None;
+                self.persist_state();
+                self.current_pending_tasks = None;
+                self.is_report_unavailable = true;
+                true
+            }
+            Msg::SessionExpired => {
+                // Other requests failing while a refresh is under way are retried afterwards.
+                // An unauthorised lookup never reaches `LookupHashDone`; it can simply be redone.
+                self.cancel_hash_lookup();
+                if self.refresh_ft.is_some() {
+                    return false;
+                }
+
+                self.begin_action("RefreshSession");
+                if let (Some(config), Some(refresh_token)) =
+                    (&self.config, &self.state.refresh_token)
+                {
+                    self.refresh_ft = Some(
+                        self.fetch_service.fetch(
+                            request_builder(
+                                config,
+                                "POST",
+                                &format!("{}/v1/auth/refresh", config.api_url),
+                                &self.request_id,
+                            )
+                            .header("Content-Type", "application/json")
+                            .body(Json(&RefreshRequest { refresh_token }))
+                            .unwrap(),
+                            self.link.send_back(
+                                move |response: Response<Json<Result<RefreshResponse, Error>>>| {
+                                    let (meta, Json(data)) = response.into_parts();
+                                    if meta.status.is_success() {
+                                        Msg::RefreshSessionDone(data)
+                                    } else {
+                                        Msg::RefreshSessionDone(Err(format_err!(
+                                            "{}: could not refresh session",
+                                            meta.status
+                                        )))
+                                    }
+                                },
+                            ),
+                        ),
+                    );
+                    return false;
+                }
+
+                self.clear_session();
+                self.modal = Some(Modal {
+                    title: "Session expired".into(),
+                    body: "Your session has expired, please login again.".into(),
+                    actions: vec![ModalAction {
+                        label: "OK",
+                        class: "button is-info",
+                        msg: || Msg::DismissModal,
+                    }],
+                });
+                true
+            }
+            Msg::RefreshSessionDone(Ok(refresh_response)) => {
+                self.refresh_ft = None;
+                self.state.token = Some(refresh_response.token);
+                if refresh_response.refresh_token.is_some() {
+                    self.state.refresh_token = refresh_response.refresh_token;
+                }
+                self.persist_state();
+
+                // Refetching profiles also resumes polling the stored report.
+                self.link.send_self(Msg::FetchProfiles);
+                false
+            }
+            Msg::RefreshSessionDone(Err(_)) => {
+                self.refresh_ft = None;
+                self.state.refresh_token = None;
+                self.link.send_self(Msg::SessionExpired);
+                false
+            }
+            Msg::TestConnection => {
+                self.begin_action("TestConnection");
+                if let Some(config) = &self.config {
+                    self.is_connection_testing = true;
+                    self.connection_test_result = None;
+
+                    self.health_ft = Some(
+                        self.fetch_service.fetch(
+                            request_builder(
+                                config,
+                                "GET",
+                                &format!("{}/v1/health", config.api_url),
+                                &self.request_id,
+                            )
+                            .body(Nothing)
+                            .unwrap(),
+                            self.link.send_back(move |response: Response<Nothing>| {
+                                let (meta, _) = response.into_parts();
+                                if meta.status.is_success() {
+                                    Msg::TestConnectionDone(Ok(()))
+                                } else if meta.status == StatusCode::REQUEST_TIMEOUT {
+                                    // Also what the fetch service reports on network errors.
+                                    Msg::TestConnectionDone(Err(format_err!(
+                                        "Could not reach the API"
+                                    )))
+                                } else {
+                                    Msg::TestConnectionDone(Err(format_err!(
+                                        "API answered {}",
+                                        meta.status
+                                    )))
+                                }
+                            }),
+                        ),
+                    );
+                };
+                true
+            }
+            Msg::TestConnectionDone(result) => {
+                self.health_ft = None;
+                self.is_connection_testing = false;
+                self.connection_test_result = Some(result.map_err(|error| error.to_string()));
+                true
+            }
+            Msg::DismissStorageNotice => {
+                self.is_storage_notice_dismissed = true;
+                true
+            }
+            Msg::DismissStorageWriteWarning => {
+                self.storage_write_warning = None;
+                true
+            }
+            Msg::DismissModal => {
+                self.modal = None;
+                true
+            }
+            Msg::MaintenanceStarted(message) => {
+                // The stored report id is kept, so polling resumes once profiles load again.
+                if !self.transition(Scene::Maintenance) {
+                    return false;
+                }
+                self.mark_reconnected();
+                self.maintenance_message = message;
+
+                if self.maintenance_it.is_none() {
+                    self.maintenance_it = Some(self.interval_service.spawn(
+                        std::time::Duration::from_millis(MAINTENANCE_RETRY_MS),
+                        self.link.send_back(|_| Msg::FetchProfiles),
+                    ));
+                }
+                true
+            }
+            Msg::Reload => {
+                js! { @(no_return) location.reload(); }
+                false
+            }
+            Msg::ShowHistory => {
+                if !self.transition(Scene::History) {
+                    return false;
+                }
+                self.claim_fetch_slot("ShowHistory");
+
+                if let Some(config) = &self.config {
+                    self.history_error = None;
+
+                    let uri = format!("{}/v1/reports", config.api_url);
+                    self.last_request = Some(RecordedRequest::new(
+                        "GET",
+                        &uri,
+                        &self.state.token,
+                        RequestBody::Empty,
+                    ));
+
+                    self.ft = Some(
+                        self.fetch_service.fetch(
+                            request_builder(config, "GET", &uri, &self.request_id)
+                                .header(
+                                    "Authorization",
+                                    self.state.token.as_ref().unwrap().to_owned(),
+                                )
+                                .body(Nothing)
+                                .unwrap(),
+                            self.link.send_back(
+                                move |response: Response<Json<Result<ReportsResponse, Error>>>| {
+                                    let (meta, Json(response)) = response.into_parts();
+                                    if meta.status.is_success() {
+                                        Msg::FetchReportsDone(response)
+                                    } else if meta.status == StatusCode::UNAUTHORIZED {
+                                        Msg::SessionExpired
+                                    } else {
+                                        Msg::FetchReportsDone(Err(format_err!(
+                                            "{}: could not fetch reports",
+                                            meta.status
+                                        )))
+                                    }
+                                },
+                            ),
+                        ),
+                    );
+                };
+                true
+            }
+            Msg::CloseHistory => self.transition(Scene::LoggedIn),
+            Msg::FetchReportsDone(Ok(reports_response)) => {
+                self.history_reports = Some(reports_response.reports);
+                true
+            }
+            Msg::FetchLimits => {
+                self.begin_action("FetchLimits");
+                if let Some(config) = &self.config {
+                    let uri = format!("{}/v1/limits", config.api_url);
+                    self.limits_ft = Some(
+                        self.fetch_service.fetch(
+                            request_builder(config, "GET", &uri, &self.request_id)
+                                .header(
+                                    "Authorization",
+                                    self.state.token.as_ref().unwrap().to_owned(),
+                                )
+                                .body(Nothing)
+                                .unwrap(),
+                            self.link.send_back(
+                                move |response: Response<Json<Result<UploadLimits, Error>>>| {
+                                    let (meta, Json(response)) = response.into_parts();
+                                    if meta.status.is_success() {
+                                        Msg::FetchLimitsDone(response)
+                                    } else {
+                                        Msg::FetchLimitsDone(Err(format_err!(
+                                            "{}: could not fetch limits",
+                                            meta.status
+                                        )))
+                                    }
+                                },
+                            ),
+                        ),
+                    );
+                };
+                false
+            }
+            Msg::FetchLimitsDone(response) => {
+                self.limits_ft = None;
+                // Older servers have no limits endpoint, the config value then applies.
+                self.upload_limits = Some(response.unwrap_or_default());
+                true
+            }
+            Msg::FetchReportsDone(Err(_)) => {
+                self.history_error = Some("Could not fetch reports".into());
+                true
+            }
+            Msg::HistoryHashFilterChange(value) => {
+                self.history_hash_filter = value;
+                true
+            }
+            Msg::HistoryDateFromChange(value) => {
+                self.history_date_from = NaiveDate::parse_from_str(&value, "%Y-%m-%d").ok();
+                true
+            }
+            Msg::HistoryDateToChange(value) => {
+                self.history_date_to = NaiveDate::parse_from_str(&value, "%Y-%m-%d").ok();
+                true
+            }
+            Msg::ClearResults => {
+                self.clear_results();
+                self.is_viewing_previous_report = false;
+                self.create_report_error = None;
+                self.create_report_rejection = None;
+                // Cleared reports are not resumed on the next page load either.
+                self.state.report_id = None;
+                self.persist_state();
+                true
+            }
+            Msg::ScanAnotherSameProfiles => {
+                self.clear_results();
+
+                js! { @(no_return)
+                    var input = document.getElementById("file-input");
+                    if (input) {
+                        input.value = "";
+                        input.click();
+                    }
+                }
+                true
+            }
+            Msg::CopyReportId(report_id) => {
+                copy_to_clipboard_then(
+                    report_id.to_string(),
+                    self.link.send_back(Msg::CopyReportIdDone),
+                );
+                false
+            }
+            Msg::CopyReportIdDone(true) => {
+                self.copied_tt = Some(self.timeout_service.spawn(
+                    std::time::Duration::from_millis(2000),
+                    self.link.send_back(|_| Msg::HideReportIdCopied),
+                ));
+                true
+            }
+            Msg::CopyReportIdDone(false) => {
+                select_element_text("report-id");
+                false
+            }
+            Msg::HideReportIdCopied => {
+                self.copied_tt = None;
+                true
+            }
+            Msg::HideWelcomeBack => {
+                self.welcome_back_tt = None;
+                true
+            }
+            Msg::CopyLastRequestAsCurl => {
+                if let (Some(config), Some(request)) = (&self.config, &self.last_request) {
+                    copy_to_clipboard(request.to_curl(config.debug_include_token));
+                }
+                false
+            }
+            Msg::DownloadConfigTemplate => {
+                match serde_json::to_string_pretty(&Config::template()) {
+                    Ok(template) => download_bytes(
+                        "config.json".into(),
+                        "application/json",
+                        template.as_bytes(),
+                    ),
+                    Err(error) => self
+                        .console_service
+                        .error(&format!("Could not build the config template: {}", error)),
+                }
+                false
+            }
+            Msg::DownloadPdf => {
+                #[cfg(feature = "pdf_export")]
+                self.download_pdf();
+                false
+            }
+            Msg::DownloadBundle => {
+                let bundle = match (
+                    &self.current_report_hash,
+                    self.current_bundle_file(),
+                    &self.current_pending_tasks,
+                ) {
+                    (Some(hash), Some(file), Some(tasks)) => {
+                        let file_name = self
+                            .session_reports
+                            .iter()
+                            .find(|x| Some(x.report_id) == self.current_report_id)
+                            .map(|x| x.file_name.as_str())
+                            .filter(|x| !x.is_empty())
+                            .unwrap_or(hash);
+                        Some((
+                            format!("{}.zip", hash),
+                            build_bundle(file_name, file, tasks),
+                        ))
+                    }
+                    _ => None,
+                };
+
+                match bundle {
+                    Some((download_name, Ok(bundle))) => {
+                        download_bytes(download_name, "application/zip", &bundle);
+                        false
+                    }
+                    Some((_, Err(_))) => {
+                        self.create_report_error = Some("Could not build the bundle".into());
+                        true
+                    }
+                    None => {
+                        self.create_report_error =
+                            Some("The original file is not available for a bundle".into());
+                        true
+                    }
+                }
+            }
+            Msg::TransientFailure(target) => {
+                self.is_polling_fetch_in_flight = false;
+                self.reconnect_attempts += 1;
+
+                if self.reconnect_attempts > MAX_RECONNECT_ATTEMPTS {
+                    self.mark_reconnected();
+                    match target {
+                        RetryTarget::Profiles => {
+                            self.fetch_profiles_error = Some("Could not reach the server".into());
+                        }
+                        RetryTarget::Poll => {
+                            self.stop_polling();
+                            self.is_connection_lost = true;
+                        }
+                    }
+                    return true;
+                }
+
+                self.is_reconnecting = true;
+
+                // Polling retries on its own at the next tick.
+                if target == RetryTarget::Profiles {
+                    self.tt = Some(self.timeout_service.spawn(
+                        std::time::Duration::from_millis(RECONNECT_DELAY_MS),
+                        self.link.send_back(|_| Msg::FetchProfiles),
+                    ));
+                }
+                true
+            }
+            Msg::KeepWaiting => {
+                self.is_connection_lost = false;
+
+                if let Some(report_id) = self.current_report_id {
+                    self.link.send_self(Msg::FetchTasks(report_id));
+                    self.start_polling(report_id);
+                }
+                true
+            }
+            Msg::RetryFailed => {
+                let report_id = match self.current_report_id {
+                    Some(report_id) => report_id,
+                    None => return false,
+                };
+
+                let machine_names = self.failed_machine_names();
+                if machine_names.is_empty() {
+                    return false;
+                }
+                let profiles = profiles_query_value(machine_names);
+
+                self.claim_fetch_slot("RetryFailed");
+                self.retry_profiles(report_id, &profiles);
+                true
+            }
+            Msg::RetryTask(profile_id) => {
+                let report_id = match self.current_report_id {
+                    Some(report_id) => report_id,
+                    None => return false,
+                };
+
+                let machine_name = self
+                    .fetched_profiles
+                    .iter()
+                    .flat_map(|x| &x.profiles)
+                    .find(|x| x.id == profile_id)
+                    .map(|x| x.machine_name.as_str());
+                let profiles = match machine_name {
+                    Some(machine_name) => profiles_query_value(vec![machine_name]),
+                    None => return false,
+                };
+
+                self.claim_fetch_slot("RetryTask");
+                self.retry_profiles(report_id, &profiles);
+                true
+            }
+            Msg::RetryFailedDone(report_id, Ok(())) => {
+                self.is_retry_failed_loading = false;
+                self.audit(format!("Retried failed engines of report #{}", report_id));
+
+                if let Some(session_report) = self
+                    .session_reports
+                    .iter_mut()
+                    .find(|x| x.report_id == report_id)
+                {
+                    session_report.summary = None;
+                }
+
+                self.state.report_id = Some(report_id);
+                self.persist_state();
+                self.link.send_self(Msg::FetchTasks(report_id));
+                self.start_polling(report_id);
+                true
+            }
+            Msg::RetryFailedDone(_, Err(_)) => {
+                self.is_retry_failed_loading = false;
+                self.create_report_error = Some("Could not retry the failed engines".into());
+                true
+            }
+            Msg::ShowSessionReport(report_id) => {
+                self.is_report_unavailable = false;
+                self.link.send_self(Msg::FetchTasks(report_id));
+                self.start_polling(report_id);
+                false
+            }
+            Msg::ToggleDensity => {
+                self.settings.compact_table = !self.settings.compact_table;
+                self.persist_settings();
+                true
+            }
+            Msg::DismissLoginHint => {
+                self.settings.login_hint_dismissed = true;
+                self.persist_settings();
+                true
+            }
+            Msg::JumpToDetection => {
+                if self.scene != Scene::LoggedIn {
+                    return false;
+                }
+
+                // Each jump moves on to the next detection, wrapping around at the end.
+                let detected = self.detected_profile_ids();
+                let next = match self
+                    .highlighted_profile_id
+                    .and_then(|id| detected.iter().position(|x| *x == id))
+                {
+                    Some(index) => detected.get(index + 1).or_else(|| detected.first()),
+                    None => detected.first(),
+                };
+                let profile_id = match next {
+                    Some(profile_id) => *profile_id,
+                    None => return false,
+                };
+
+                self.highlighted_profile_id = Some(profile_id);
+                scroll_to_element(&format!("profile-{}", profile_id));
+                true
+            }
+            Msg::NoOp => false,
+            _ => false,
+        }
+    }
+}
+
+impl<A: Api> Model<A> {
+    /// Builds the component around whatever delivers its messages and stores its state, so
+    /// tests can run it without a browser. `create` sets up the page listeners first.
+    fn new(
+        link: MessageLink<Self>,
+        storage_service: LocalStore,
+        is_mobile: bool,
+        embed_mode: bool,
+    ) -> Self {
+        let state = {
+            if let Json(Ok(state)) = storage_service.restore(KEY) {
+                state
+            } else {
+                State {
+                    token: None,
+                    refresh_token: None,
+                    report_id: None,
+                }
+            }
+        };
+
+        let settings = {
+            if let Json(Ok(settings)) = storage_service.restore(SETTINGS_KEY) {
+                settings
+            } else {
+                Settings::default()
+            }
+        };
+
+        let loginregister_form = LoginRegisterFormData {
+            username: settings.remembered_username.clone().unwrap_or_default(),
+            password: String::new(),
+        };
+
+        let mut console_service = ConsoleService::new();
+        console_service.log(&format!("web-client {}", build_info()));
+
+        // Tells a session picked up from storage apart from a login made on this page load.
+        let session_restored = state.token.is_some();
+        if session_restored {
+            console_service.log("Session restored from storage");
+        }
+
+        Self {
+            link,
+            state,
+            settings,
+            fetch_service: A::default(),
+            console_service,
+            interval_service: IntervalService::new(),
+            timeout_service: TimeoutService::new(),
+            storage_service,
+            is_storage_notice_dismissed: false,
+            storage_write_warning: None,
+            scene: Scene::Loading,
+            ft: None,
+            ft_label: "",
+            report_ft: None,
+            lookup_ft: None,
+            limits_ft: None,
+            request_id: new_request_id(),
+            refresh_ft: None,
+            background_fts: HashMap::new(),
+            health_ft: None,
+            last_request: None,
+            rt: None,
+            it: None,
+            tt: None,
+            logout_tt: None,
+            copied_tt: None,
+            idle_it: None,
+            profiles_refresh_it: None,
+            idle_deadline: None,
+            is_idle_warning_shown: false,
+            maintenance_it: None,
+            background_it: None,
+            maintenance_message: None,
+            beforeunload_listener: None,
+            config: None,
+            config_fetch_attempts: 0,
+            modal: None,
+            loginregister_error: None,
+            loginregister_form,
+            auth_view: AuthView::LoginRegister,
+            password_reset_form: PasswordResetFormData::default(),
+            password_reset_message: None,
+            password_reset_error: None,
+            is_password_reset_loading: false,
+            logout_warning: None,
+            logout_everywhere_error: None,
+            fetched_profiles: None,
+            fetch_profiles_error: None,
+            enabled_profiles: HashSet::new(),
+            show_detections_only: false,
+            show_muted_profiles: false,
+            group_by_severity: true,
+            is_clean_expanded: false,
+            dragged_profile_id: None,
+            highlighted_profile_id: None,
+            is_mobile,
+            embed_mode,
+            expanded_profile_configs: HashSet::new(),
+            config_override_drafts: HashMap::new(),
+            config_overrides: HashMap::new(),
+            config_override_errors: HashMap::new(),
+            full_profile_configs: HashSet::new(),
+            is_large_selection_acknowledged: false,
+            is_file_uploading: false,
+            is_url_submitting: false,
+            is_retry_failed_loading: false,
+            is_report_creating: false,
+            scan_url: String::new(),
+            scan_input_kind: InputKind::File,
+            lookup_hash: String::new(),
+            lookup_reports: None,
+            lookup_error: None,
+            is_lookup_loading: false,
+            is_connection_testing: false,
+            connection_test_result: None,
+            create_report_error: None,
+            create_report_rejection: None,
+            is_register_disabled: false,
+            is_register_loading: false,
+            is_login_loading: false,
+            is_login_disabled: false,
+            is_guest_login_loading: false,
+            is_guest: false,
+            session_restored,
+            welcome_back_tt: None,
+            is_logout_loading: false,
+            is_logout_disabled: false,
+            is_logout_everywhere_loading: false,
+            current_report_id: None,
+            current_report_hash: None,
+            current_report_created_when: None,
+            previous_verdicts: None,
+            verdict_cache: HashMap::new(),
+            current_pending_tasks: None,
+            previous_report: None,
+            report_tasks: HashMap::new(),
+            is_viewing_previous_report: false,
+            poll_attempts: 0,
+            poll_interval_ms: DEFAULT_POLL_INTERVAL_MS,
+            reconnect_attempts: 0,
+            is_reconnecting: false,
+            is_connection_lost: false,
+            paused_report_id: None,
+            manual_poll_report_id: None,
+            is_status_rollup_unavailable: false,
+            is_polling_fetch_in_flight: false,
+            tasks_fetch_seq: 0,
+            applied_tasks_seq: 0,
+            timed_out_task_ids: HashSet::new(),
+            is_report_unavailable: false,
+            scan_announcement: String::new(),
+            pending_file_name: None,
+            pending_file: None,
+            bundle_file: None,
+            session_reports: Vec::new(),
+            audit_log: Vec::new(),
+            history_reports: None,
+            history_error: None,
+            upload_limits: None,
+            history_hash_filter: String::new(),
+            history_date_from: None,
+            history_date_to: None,
+        }
+    }
+
+    fn feature_enabled(&self, name: &str) -> bool {
+        match &self.config {
+            Some(config) => config.feature_enabled(name),
+            None => false,
+        }
+    }
+
+    fn show_config_error(&mut self, detail: Option<String>) {
+        let mut body = "Could not fetch configuration, please reload to try again.".to_owned();
+        if let Some(detail) = detail {
+            body = format!("{}\n{}", body, detail);
+        }
+
+        self.transition(Scene::FetchConfigError);
+        self.modal = Some(Modal {
+            title: "Configuration error".into(),
+            body,
+            actions: vec![ModalAction {
+                label: "Reload",
+                class: "button is-danger",
+                msg: || Msg::Reload,
+            }],
+        });
+    }
+
+    /// Drops the token and everything tied to the session, then returns to the login scene.
+    fn clear_session(&mut self) {
+        self.clear_results();
+        self.session_reports.clear();
+        self.bundle_file = None;
+        self.audit_log.clear();
+        self.previous_report = None;
+        self.is_viewing_previous_report = false;
+        self.report_tasks.clear();
+        self.background_it = None;
+        self.stop_idle_tracking();
+        self.history_reports = None;
+        self.upload_limits = None;
+        self.state.token = None;
+        self.state.refresh_token = None;
+        self.state.report_id = None;
+        self.persist_state();
+        self.is_guest = false;
+        self.session_restored = false;
+        self.welcome_back_tt = None;
+        self.transition(Scene::LoginRegister);
+    }
+
+    /// Moves to `to` when `is_scene_change_allowed` agrees, cancelling whatever the scene being
+    /// left still has in flight. Returns whether the scene changed.
+    fn transition(&mut self, to: Scene) -> bool {
+        if !is_scene_change_allowed(self.scene, to, self.state.token.is_some()) {
+            self.console_service.warn(&format!(
+                "Refused scene change from {:?} to {:?}",
+                self.scene, to
+            ));
+            return false;
+        }
+
+        let from = std::mem::replace(&mut self.scene, to);
+        if from == to {
+            return true;
+        }
+
+        if from == Scene::History {
+            // Only the reports request can be using the shared slot here.
+            self.ft = None;
+        }
+        if from == Scene::Maintenance {
+            self.maintenance_it = None;
+            self.maintenance_message = None;
+        }
+        match to {
+            Scene::Maintenance => {
+                self.stop_polling();
+                self.profiles_refresh_it = None;
+            }
+            Scene::LoginRegister | Scene::FetchConfigError => {
+                self.stop_polling();
+                self.profiles_refresh_it = None;
+                self.ft = None;
+                self.report_ft = None;
+                self.cancel_hash_lookup();
+                self.limits_ft = None;
+                self.refresh_ft = None;
+                self.background_fts.clear();
+                self.tt = None;
+                self.maintenance_it = None;
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn audit(&mut self, action: String) {
+        self.audit_log.push(AuditEntry {
+            timestamp: Utc::now(),
+            action,
+        });
+    }
+
+    /// Stores the selection by profile id, which survives machine names being renamed.
+    fn persist_enabled_profiles(&mut self) {
+        if self.fetched_profiles.is_some() {
+            self.settings.enabled_profile_ids = Some(self.enabled_profiles.clone());
+            self.persist_settings();
+        }
+    }
+
+    /// Enabled and total engine counts when only some of the fetched profiles are enabled.
+    /// Muted profiles are left out, they were excluded on purpose.
+    fn partial_profile_selection(&self) -> Option<(usize, usize)> {
+        let total = self
+            .fetched_profiles
+            .as_ref()?
+            .profiles
+            .iter()
+            .filter(|x| !self.settings.muted_profile_ids.contains(&x.id))
+            .count();
+        let enabled = self.enabled_profiles.len();
+
+        if enabled < total {
+            Some((enabled, total))
+        } else {
+            None
+        }
+    }
+
+    fn enabled_machine_names(&self) -> HashSet<&str> {
+        self.fetched_profiles
+            .iter()
+            .flat_map(|x| x.profiles.iter())
+            .filter(|x| self.enabled_profiles.contains(&x.id))
+            .map(|x| x.machine_name.as_str())
+            .collect()
+    }
+
+    /// Only the username is ever remembered, never the password.
+    fn persist_remembered_username(&mut self) {
+        if self.settings.remember_username {
+            self.settings.remembered_username = Some(self.loginregister_form.username.to_owned());
+            self.persist_settings();
+        }
+    }
+
+    fn persist_state(&mut self) {
+        if self.is_guest {
+            return;
+        }
+
+        // The in-memory session keeps working either way; only the next page load is affected.
+        match self.storage_service.store(KEY, Json(&self.state)) {
+            Ok(()) => self.storage_write_warning = None,
+            Err(error) => {
+                self.console_service.warn(&error.to_string());
+                if self.state.token.is_some() {
+                    self.storage_write_warning = Some(
+                        "Couldn't save your session — you'll need to log in again next time".into(),
+                    );
+                }
+            }
+        }
+    }
+
+    fn persist_settings(&mut self) {
+        if let Err(error) = self
+            .storage_service
+            .store(SETTINGS_KEY, Json(&self.settings))
+        {
+            self.console_service.warn(&error.to_string());
+            if self.storage_write_warning.is_none() {
+                self.storage_write_warning = Some(
+                    "Couldn't save your settings — they only last until the page is closed".into(),
+                );
+            }
+        }
+    }
+
+    /// Leaves out enabled engines that cannot scan this kind of input.
+    fn create_report_request(
+        &self,
+        config: &Config,
+        input_kind: InputKind,
+    ) -> (String, http::request::Builder) {
+        let machine_names = self
+            .fetched_profiles
+            .iter()
+            .flat_map(|x| x.profiles.iter())
+            .filter(|x| {
+                self.enabled_profiles.contains(&x.id) && is_module_applicable(&x.module, input_kind)
+            })
+            .map(|x| x.machine_name.as_str());
+
+        profiles_request_builder(
+            config,
+            &config.create_report_uri(),
+            &profiles_query_value(machine_names),
+            &self.request_id,
+        )
+    }
+
+    /// Config overrides of the enabled profiles, sent along with a new report.
+    fn enabled_config_overrides(&self) -> HashMap<String, serde_json::Value> {
+        let enabled_machine_names = self.enabled_machine_names();
+
+        self.config_overrides
+            .iter()
+            .filter(|(machine_name, _)| enabled_machine_names.contains(machine_name.as_str()))
+            .map(|(machine_name, config)| (machine_name.to_owned(), config.clone()))
+            .collect()
+    }
+
+    /// The warning to show when more profiles are enabled than the backend recommends.
+    fn large_selection_warning(&self) -> Option<String> {
+        let max = self.config.as_ref()?.max_recommended_profiles?;
+        let count = self.enabled_profiles.len();
+
+        if count > max {
+            Some(format!("Scanning with {} engines may be slow", count))
+        } else {
+            None
+        }
+    }
+
+    /// Whether a profile row should be shown with the current results filter.
+    fn is_profile_visible(&self, profile: &Profile) -> bool {
+        if !self.show_muted_profiles && self.settings.muted_profile_ids.contains(&profile.id) {
+            return false;
+        }
+
+        if !self.show_detections_only {
+            return true;
+        }
+
+        match self.displayed_tasks() {
+            Some(tasks) => tasks.iter().any(|x| {
+                x.profile_id == profile.id
+                    && (x.status == "detected" || x.status == "error" || x.status == "timeout")
+            }),
+            None => true,
+        }
+    }
+
+    /// Tasks of the report shown in the table, which is the previous one while toggled to it.
+    fn displayed_tasks(&self) -> Option<&Vec<Task>> {
+        match &self.previous_report {
+            Some(previous_report) if self.is_viewing_previous_report => {
+                Some(&previous_report.tasks)
+            }
+            _ => self.current_pending_tasks.as_ref(),
+        }
+    }
+
+    /// Describes tasks whose status changed since the previous poll, for screen readers.
+    fn describe_task_changes(&self, tasks: &[Task]) -> String {
+        tasks
+            .iter()
+            .filter(|task| {
+                let previous = self
+                    .current_pending_tasks
+                    .as_ref()
+                    .and_then(|previous| previous.iter().find(|x| x.id == task.id));
+
+                match previous {
+                    Some(previous) => previous.status != task.status,
+                    None => task.status != "new",
+                }
+            })
+            .map(|task| {
+                let engine = self
+                    .fetched_profiles
+                    .as_ref()
+                    .and_then(|x| x.profiles.iter().find(|x| x.id == task.profile_id))
+                    .map_or("unknown", |x| x.human_name.as_str());
+
+                format!("Engine {}: {}", engine, task_status_label(&task.status))
+            })
+            .collect::<Vec<String>>()
+            .join(". ")
+    }
+
+    /// Forgets the displayed report without touching the profile selection.
+    fn clear_results(&mut self) {
+        self.stop_polling();
+        self.mark_reconnected();
+        self.is_connection_lost = false;
+        set_document_title(0);
+        self.current_report_id = None;
+        self.current_report_hash = None;
+        self.current_report_created_when = None;
+        self.previous_verdicts = None;
+        self.current_pending_tasks = None;
+        self.timed_out_task_ids.clear();
+        self.is_report_unavailable = false;
+        self.highlighted_profile_id = None;
+    }
+
+    /// The step the phase indicator highlights, from reading the file to the finished scan.
+    /// `None` hides the indicator when no scan is under way or shown.
+    fn scan_phase(&self) -> Option<ScanPhase> {
+        if self.is_report_creating {
+            Some(ScanPhase::Uploading)
+        } else if self.is_file_uploading {
+            Some(ScanPhase::Reading)
+        } else if self.is_polling() {
+            Some(ScanPhase::Scanning)
+        } else if self.is_scan_complete() {
+            Some(ScanPhase::Done)
+        } else {
+            None
+        }
+    }
+
+    /// Whether the displayed report has finished and has results to show.
+    fn is_scan_complete(&self) -> bool {
+        !self.is_polling()
+            && self
+                .current_pending_tasks
+                .as_ref()
+                .is_some_and(|x| !x.is_empty())
+    }
+
+    /// Fetches the report metadata, used to compare verdicts with earlier scans of the same file.
+    fn fetch_report(&mut self, report_id: i64) {
+        if let Some(config) = &self.config {
+            let uri = format!("{}/v1/reports/{}", config.api_url, report_id);
+            self.last_request = Some(RecordedRequest::new(
+                "GET",
+                &uri,
+                &self.state.token,
+                RequestBody::Empty,
+            ));
+
+            self.report_ft = Some(
+                self.fetch_service.fetch(
+                    request_builder(config, "GET", &uri, &self.request_id)
+                        .header(
+                            "Authorization",
+                            self.state.token.as_ref().unwrap().to_owned(),
+                        )
+                        .body(Nothing)
+                        .unwrap(),
+                    self.link
+                        .send_back(move |response: Response<Json<Result<Report, Error>>>| {
+                            let (meta, Json(response)) = response.into_parts();
+                            if meta.status.is_success() {
+                                Msg::FetchReportDone(response)
+                            } else {
+                                Msg::FetchReportDone(Err(format_err!(
+                                    "{}: could not fetch report",
+                                    meta.status
+                                )))
+                            }
+                        }),
+                ),
+            );
+        }
+    }
+
+    /// The uploaded bytes of the displayed report, if they were kept for a bundle.
+    fn current_bundle_file(&self) -> Option<&[u8]> {
+        self.bundle_file
+            .as_ref()
+            .filter(|(report_id, _)| Some(*report_id) == self.current_report_id)
+            .map(|(_, file)| file.as_slice())
+    }
+
+    #[cfg(feature = "pdf_export")]
+    fn download_pdf(&self) {
+        if let (Some(profiles_response), Some(tasks)) =
+            (&self.fetched_profiles, &self.current_pending_tasks)
+        {
+            let file_name = self
+                .session_reports
+                .iter()
+                .find(|x| Some(x.report_id) == self.current_report_id)
+                .map(|x| x.file_name.as_str())
+                .unwrap_or("");
+            let lines = pdf::report_lines(
+                file_name,
+                self.current_report_hash.as_deref(),
+                self.current_report_created_when,
+                tasks,
+                &profiles_response.profiles,
+            );
+            let download_name =
+                format!("report-{}.pdf", self.current_report_id.unwrap_or_default());
+
+            download_bytes(download_name, "application/pdf", &pdf::render(&lines));
+        }
+    }
+
+    /// The server's own limit wins over the config one; `None` means no client-side check.
+    fn max_upload_bytes(&self) -> Option<u64> {
+        self.upload_limits
+            .as_ref()
+            .and_then(|x| x.max_upload_bytes)
+            .or_else(|| self.config.as_ref().and_then(|x| x.max_upload_bytes))
+    }
+
+    /// Refuses a file over the upload limit before it is read, rather than after the upload.
+    fn upload_size_error(&self, file_name: &str, size: u64) -> Option<String> {
+        self.max_upload_bytes()
+            .filter(|&limit| size > limit)
+            .map(|limit| {
+                format!(
+                    "{} is too large, the limit is {}",
+                    file_name,
+                    format_file_size(limit)
+                )
+            })
+    }
+
+    fn read_file(&mut self, file: File) {
+        if !self.config_override_errors.is_empty() {
+            self.create_report_error = Some("Fix the invalid config overrides first".into());
+            return;
+        }
+
+        if let Some(error) = self.upload_size_error(&file.name(), file.len()) {
+            self.create_report_error = Some(error);
+            return;
+        }
+
+        self.is_file_uploading = true;
+        self.create_report_error = None;
+
+        self.rt = Some(read_file_data(
+            file,
+            self.link
+                .send_back(|result: Result<FileData, Error>| match result {
+                    Ok(file_data) => Msg::CreateReport(file_data),
+                    Err(_) => Msg::ReadFileFailed,
+                }),
+        ));
+    }
+
+    fn start_polling(&mut self, report_id: i64) {
+        self.poll_attempts = 0;
+        self.timed_out_task_ids.clear();
+
+        if self.current_report_id != Some(report_id) {
+            if let (true, Some(previous_id)) = (self.is_scan_complete(), self.current_report_id) {
+                self.previous_report =
+                    self.current_pending_tasks
+                        .clone()
+                        .map(|tasks| PreviousReport {
+                            report_id: previous_id,
+                            tasks,
+                        });
+            }
+            // A scan that is still running keeps being polled in its own tab.
+            self.stash_current_report();
+            self.is_viewing_previous_report = false;
+
+            self.current_report_id = Some(report_id);
+            self.current_report_hash = None;
+            self.current_report_created_when = None;
+            self.previous_verdicts = None;
+            self.current_pending_tasks = self.report_tasks.remove(&report_id);
+            self.background_fts.remove(&report_id);
+        }
+
+        self.paused_report_id = None;
+        if self.is_auto_poll_enabled() {
+            self.spawn_poll_interval(report_id);
+        } else {
+            self.manual_poll_report_id = Some(report_id);
+        }
+        self.update_background_polling();
+
+        // Ask the browser to confirm leaving the page while a scan is running.
+        if self.beforeunload_listener.is_none() {
+            // Registered from JavaScript so the component also runs where there is no window.
+            self.beforeunload_listener = Some(js! {
+                var listener = function(event) {
+                    event.preventDefault();
+                    event.returnValue = "";
+                };
+                window.addEventListener("beforeunload", listener);
+                return listener;
+            });
+        }
+    }
+
+    /// Alerts users who opted in that a scan finished, for when they switched away meanwhile.
+    fn notify_scan_complete(&self, report_id: i64, summary: &str) {
+        if !self.settings.notify_on_completion || !self.feature_enabled("completion_notifications")
+        {
+            return;
+        }
+
+        play_completion_sound();
+        show_notification(
+            "Scan complete",
+            format!("Report #{}: {}", report_id, summary),
+        );
+    }
+
+    /// Asks the server to run the given engines of `report_id` again. Shared by the retry-all
+    /// button and the per-row retry; both end in `Msg::RetryFailedDone`.
+    fn retry_profiles(&mut self, report_id: i64, profiles: &str) {
+        if let Some(config) = &self.config {
+            self.is_retry_failed_loading = true;
+            self.create_report_error = None;
+
+            let (uri, mut builder) = profiles_request_builder(
+                config,
+                &format!("{}/v1/reports/{}/retry", config.api_url, report_id),
+                profiles,
+                &self.request_id,
+            );
+            self.last_request = Some(RecordedRequest::new(
+                "POST",
+                &uri,
+                &self.state.token,
+                RequestBody::Empty,
+            ));
+
+            self.ft = Some(
+                self.fetch_service.fetch(
+                    builder
+                        .header(
+                            "Authorization",
+                            self.state.token.as_ref().unwrap().to_owned(),
+                        )
+                        .body(Nothing)
+                        .unwrap(),
+                    self.link.send_back(move |response: Response<Nothing>| {
+                        let (meta, _) = response.into_parts();
+                        if meta.status.is_success() {
+                            Msg::RetryFailedDone(report_id, Ok(()))
+                        } else if meta.status == StatusCode::UNAUTHORIZED {
+                            Msg::SessionExpired
+                        } else {
+                            Msg::RetryFailedDone(
+                                report_id,
+                                Err(format_err!("{}: could not retry tasks", meta.status)),
+                            )
+                        }
+                    }),
+                ),
+            );
+        }
+    }
+
+    /// Machine names of the profiles whose task errored or timed out in the shown report.
+    fn failed_machine_names(&self) -> Vec<&str> {
+        let tasks = match &self.current_pending_tasks {
+            Some(tasks) => tasks,
+            None => return Vec::new(),
+        };
+
+        self.fetched_profiles
+            .iter()
+            .flat_map(|x| &x.profiles)
+            .filter(|profile| {
+                tasks.iter().any(|x| {
+                    x.profile_id == profile.id && (x.status == "error" || x.status == "timeout")
+                })
+            })
+            .map(|x| x.machine_name.as_str())
+            .collect()
+    }
+
+    /// How long users may stay inactive before being logged out, `None` when the deployment does
+    /// not log idle users out.
+    fn idle_logout_duration(&self) -> Option<chrono::Duration> {
+        self.config
+            .as_ref()
+            .and_then(|x| x.idle_logout_minutes)
+            .map(|x| chrono::Duration::minutes(x as i64))
+    }
+
+    fn idle_warning_duration(&self) -> chrono::Duration {
+        let seconds = self
+            .config
+            .as_ref()
+            .and_then(|x| x.idle_warning_seconds)
+            .unwrap_or(DEFAULT_IDLE_WARNING_SECONDS);
+        chrono::Duration::seconds(seconds as i64)
+    }
+
+    fn extend_idle_deadline(&mut self, duration: Option<chrono::Duration>) {
+        if let (Some(duration), true) = (duration, self.idle_it.is_some()) {
+            self.idle_deadline = Some(Utc::now() + duration);
+        }
+    }
+
+    fn start_idle_tracking(&mut self) {
+        if self.idle_it.is_some() || self.idle_logout_duration().is_none() {
+            return;
+        }
+
+        self.idle_it = Some(self.interval_service.spawn(
+            std::time::Duration::from_secs(1),
+            self.link.send_back(|_| Msg::IdleTick),
+        ));
+        self.extend_idle_deadline(self.idle_logout_duration());
+    }
+
+    /// With `profiles_refresh_interval_s`, refetches profiles so engines added on the server show
+    /// up in long-lived sessions. The answer is reconciled like any other profiles fetch.
+    fn start_profiles_refresh(&mut self) {
+        let interval = match self
+            .config
+            .as_ref()
+            .and_then(|x| x.profiles_refresh_interval_s)
+        {
+            Some(interval) if interval > 0 => interval,
+            _ => return,
+        };
+        if self.profiles_refresh_it.is_some() {
+            return;
+        }
+
+        self.profiles_refresh_it = Some(self.interval_service.spawn(
+            std::time::Duration::from_secs(interval),
+            self.link.send_back(|_| Msg::AutoRefreshProfiles),
+        ));
+    }
+
+    fn stop_idle_tracking(&mut self) {
+        self.idle_it = None;
+        self.idle_deadline = None;
+        self.is_idle_warning_shown = false;
+    }
+
+    /// Running reports of this session other than the shown one, polled in the background.
+    fn background_report_ids(&self) -> Vec<i64> {
+        self.session_reports
+            .iter()
+            .filter(|x| x.summary.is_none() && Some(x.report_id) != self.current_report_id)
+            .map(|x| x.report_id)
+            .collect()
+    }
+
+    /// Keeps the shown report's tasks in its tab before another report takes its place.
+    fn stash_current_report(&mut self) {
+        if let (Some(report_id), Some(tasks)) =
+            (self.current_report_id, self.current_pending_tasks.take())
+        {
+            self.report_tasks.insert(report_id, tasks);
+        }
+    }
+
+    fn update_background_polling(&mut self) {
+        if self.background_report_ids().is_empty() || !self.is_auto_poll_enabled() {
+            self.background_it = None;
+            self.background_fts.clear();
+        } else if self.background_it.is_none() {
+            self.background_it = Some(self.interval_service.spawn(
+                std::time::Duration::from_millis(self.poll_interval_ms),
+                self.link.send_back(|_| Msg::PollBackgroundReports),
+            ));
+        }
+    }
+
+    fn is_fetch_slot_busy(&self) -> bool {
+        self.ft.as_ref().is_some_and(yew::services::Task::is_active)
+    }
+
+    fn cancel_hash_lookup(&mut self) {
+        self.lookup_ft = None;
+        self.is_lookup_loading = false;
+    }
+
+    /// Starts a new logical action with a fresh `X-Request-Id`. Follow-up requests of the same
+    /// action, like the report metadata fetched after an upload, keep using it.
+    fn begin_action(&mut self, label: &str) {
+        self.request_id = new_request_id();
+
+        if self.config.as_ref().is_some_and(|x| x.debug) {
+            self.console_service
+                .log(&format!("{} [request id {}]", label, self.request_id));
+        }
+    }
+
+    /// Records which request is about to take the shared `ft` slot. Replacing a request still in
+    /// flight cancels it and its callback never runs, so say so in debug mode.
+    fn claim_fetch_slot(&mut self, label: &'static str) {
+        self.begin_action(label);
+        let is_debug = self.config.as_ref().is_some_and(|x| x.debug);
+
+        if is_debug && self.is_fetch_slot_busy() {
+            self.console_service.warn(&format!(
+                "{} request dropped while in flight, replaced by {}",
+                self.ft_label, label
+            ));
+        }
+
+        self.ft_label = label;
+    }
+
+    fn mark_reconnected(&mut self) {
+        self.reconnect_attempts = 0;
+        self.is_reconnecting = false;
+    }
+
+    fn spawn_poll_interval(&mut self, report_id: i64) {
+        self.it = Some(self.interval_service.spawn(
+            std::time::Duration::from_millis(self.poll_interval_ms),
+            self.link.send_back(move |_| Msg::PollReport(report_id)),
+        ));
+    }
+
+    /// Whether a report is being polled, including while polling is paused for a hidden tab or
+    /// left to the user with `auto_poll` off.
+    fn is_polling(&self) -> bool {
+        self.it.is_some() || self.paused_report_id.is_some() || self.manual_poll_report_id.is_some()
+    }
+
+    /// Deployments can turn `auto_poll` off to have users check on reports by hand.
+    fn is_auto_poll_enabled(&self) -> bool {
+        self.config
+            .as_ref()
+            .is_none_or(|x| x.auto_poll.unwrap_or(true))
+    }
+
+    fn stop_polling(&mut self) {
+        self.it = None;
+        self.paused_report_id = None;
+        self.manual_poll_report_id = None;
+        self.is_polling_fetch_in_flight = false;
+
+        if let Some(listener) = self.beforeunload_listener.take() {
+            js! { @(no_return)
+                window.removeEventListener("beforeunload", @{listener});
+            }
+        }
+    }
+}
+
+/// Formats a byte count with binary units, e.g. `104857600` as `100 MB`.
+fn format_file_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["bytes", "KB", "MB", "GB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} bytes", bytes)
+    } else if size.fract() == 0.0 {
+        format!("{} {}", size, UNITS[unit])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// Whether a key pressed on a profile row toggles it. Only the row itself reacts, so Space on the
+/// checkbox is left to the browser and the profile is not toggled twice.
+fn is_profile_row_toggle_key(key: &str, is_row_focused: bool) -> bool {
+    is_row_focused && (key == " " || key == "Enter")
+}
+
+/// Whether the app may move between two scenes. Anything showing account data needs a token,
+/// and history is only reachable from the main scene; the login and error scenes are always
+/// reachable so a broken session can be left.
+fn is_scene_change_allowed(from: Scene, to: Scene, has_token: bool) -> bool {
+    match to {
+        Scene::LoginRegister | Scene::FetchConfigError => true,
+        Scene::Loading | Scene::Maintenance | Scene::LoggedIn => has_token,
+        Scene::History => has_token && (from == Scene::LoggedIn || from == Scene::History),
+    }
+}
+
+/// Cuts pretty-printed JSON down to roughly `max_len` bytes, ending on a line boundary so the
+/// preview stays readable. Returns `None` when the whole value fits.
+fn truncate_pretty_json(pretty: &str, max_len: usize) -> Option<&str> {
+    if pretty.len() <= max_len {
+        return None;
+    }
+
+    let mut end = max_len;
+    while !pretty.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    Some(match pretty[..end].rfind('\n') {
+        Some(newline) if newline > 0 => &pretty[..newline],
+        _ => &pretty[..end],
+    })
+}
+
+/// Picks a polling interval from the Network Information API's effective connection type, so slow
+/// links are not flooded with requests. Unknown types get no opinion.
+fn poll_interval_for_connection(effective_type: Option<&str>) -> Option<u64> {
+    match effective_type? {
+        "slow-2g" => Some(5000),
+        "2g" => Some(3000),
+        "3g" => Some(2000),
+        "4g" => Some(1000),
+        _ => None,
+    }
+}
+
+/// Final verdicts of a report, keyed by profile id.
+fn task_verdicts(tasks: &[Task]) -> HashMap<i64, String> {
+    tasks
+        .iter()
+        .filter(|x| x.status != "new" && x.status != "pending")
+        .map(|x| (x.profile_id, x.status.to_owned()))
+        .collect()
+}
+
+/// Profiles whose final verdict differs from the one recorded for an earlier scan.
+fn changed_verdicts(previous: &HashMap<i64, String>, tasks: &[Task]) -> HashSet<i64> {
+    task_verdicts(tasks)
+        .into_iter()
+        .filter(|(profile_id, status)| previous.get(profile_id).is_some_and(|x| x != status))
+        .map(|(profile_id, _)| profile_id)
+        .collect()
+}
+
+/// Reads a whole file like `ReaderService::read_file`, but reports failed reads instead of
+/// silently dropping them.
+fn read_file_data(file: File, callback: Callback<Result<FileData, Error>>) -> FileReader {
+    let file_reader = FileReader::new();
+    let reader = file_reader.clone();
+    let name = file.name();
+    let on_load_end = callback.clone();
+
+    file_reader.add_event_listener(move |_: LoadEndEvent| match reader.result() {
+        Some(FileReaderResult::ArrayBuffer(buffer)) => {
+            let array: TypedArray<u8> = buffer.into();
+            on_load_end.emit(Ok(FileData {
+                name: name.clone(),
+                content: array.to_vec(),
+            }));
+        }
+        _ => on_load_end.emit(Err(format_err!("could not read {}", name))),
+    });
+
+    if file_reader.read_as_array_buffer(&file).is_err() {
+        callback.emit(Err(format_err!("could not start reading {}", file.name())));
+    }
+
+    file_reader
+}
+
+/// A random 16 hex digit id for `X-Request-Id`, from the browser's CSPRNG when there is one.
+fn new_request_id() -> String {
+    let bytes: Vec<u8> = js! {
+        var bytes = new Uint8Array(8);
+        if (window.crypto && window.crypto.getRandomValues) {
+            window.crypto.getRandomValues(bytes);
+        } else {
+            for (var i = 0; i < bytes.length; i++) {
+                bytes[i] = Math.floor(Math.random() * 256);
+            }
+        }
+        return Array.from(bytes);
+    }
+    .try_into()
+    .unwrap_or_default();
+
+    format_request_id(&bytes)
+}
+
+fn format_request_id(bytes: &[u8]) -> String {
+    bytes.iter().map(|x| format!("{:02x}", x)).collect()
+}
+
+/// Starts a request to the backend tagged with `request_id` and with the deployment's
+/// `extra_headers` applied. Headers the client sets itself, and names or values that are not
+/// valid in HTTP, are skipped.
+fn request_builder(
+    config: &Config,
+    method: &str,
+    uri: &str,
+    request_id: &str,
+) -> http::request::Builder {
+    let mut builder = Request::builder();
+    builder
+        .method(method)
+        .uri(uri)
+        .header("X-Request-Id", request_id);
+
+    for (name, value) in config.extra_headers.iter().flatten() {
+        let is_reserved = name.eq_ignore_ascii_case("authorization")
+            || name.eq_ignore_ascii_case("content-type")
+            || name.eq_ignore_ascii_case("x-request-id");
+        let is_valid =
+            HeaderName::from_bytes(name.as_bytes()).is_ok() && HeaderValue::from_str(value).is_ok();
+
+        if !is_reserved && is_valid {
+            builder.header(name.as_str(), value.as_str());
+        }
+    }
+
+    builder
+}
+
+/// Starts a POST carrying a profile list, in the `profiles` query parameter when the URL stays
+/// short enough for servers and proxies, in the `X-Profiles` header otherwise. Returns the URI
+/// actually requested along with the builder.
+fn profiles_request_builder(
+    config: &Config,
+    base_uri: &str,
+    profiles: &str,
+    request_id: &str,
+) -> (String, http::request::Builder) {
+    let uri = format!("{}?profiles={}", base_uri, profiles);
+    if uri.len() <= MAX_QUERY_URI_LEN {
+        let builder = request_builder(config, "POST", &uri, request_id);
+        return (uri, builder);
+    }
+
+    let mut builder = request_builder(config, "POST", base_uri, request_id);
+    builder.header("X-Profiles", profiles);
+    (base_uri.to_owned(), builder)
+}
+
+/// Error details some backend endpoints return as JSON.
+#[derive(Deserialize)]
+struct ErrorBody {
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    message: Option<String>,
+}
+
+/// Body of the 503 the backend answers with while it is down for maintenance.
+#[derive(Deserialize)]
+struct MaintenanceResponse {
+    #[serde(default)]
+    maintenance: bool,
+    #[serde(default)]
+    message: Option<String>,
+}
+
+/// Tells a maintenance window apart from other server errors, which are retried as transient.
+fn parse_maintenance_response(status: StatusCode, body: &str) -> Option<MaintenanceResponse> {
+    if status != StatusCode::SERVICE_UNAVAILABLE {
+        return None;
+    }
+
+    serde_json::from_str::<MaintenanceResponse>(body)
+        .ok()
+        .filter(|x| x.maintenance)
+}
+
+/// Logout succeeds on a 2xx whose body, if any, does not describe an error.
+fn parse_logout_response(status: StatusCode, body: Text) -> Result<(), Error> {
+    let body = body.unwrap_or_default();
+    let detail = serde_json::from_str::<ErrorBody>(&body)
+        .ok()
+        .and_then(|x| x.error.or(x.message));
+
+    match detail {
+        Some(detail) => Err(format_err!("{}", detail)),
+        None if status.is_success() => Ok(()),
+        None => Err(format_err!("{}", status)),
+    }
+}
+
+fn parse_create_report_response(status: StatusCode, body: Text) -> Result<CreateResponse, Error> {
+    if status.is_success() {
+        return Ok(serde_json::from_str(&body?)?);
+    }
+
+    let body = body.unwrap_or_default();
+    match classify_create_report_rejection(status, &body) {
+        Some(rejection) => Err(rejection.into()),
+        None => Err(format_err!("{}: could not create report", status)),
+    }
+}
+
+fn encode_base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (index, byte)| {
+            bits | u32::from(*byte) << (16 - index * 8)
+        });
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(ALPHABET[(bits >> (18 - index * 6)) as usize & 0x3f] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Recognizes oversized uploads (413) and exhausted quotas (402 or 429), keeping whatever usage
+/// figures the error body carries.
+fn classify_create_report_rejection(
+    status: StatusCode,
+    body: &str,
+) -> Option<CreateReportRejection> {
+    match status {
+        StatusCode::PAYLOAD_TOO_LARGE => Some(CreateReportRejection::TooLarge),
+        StatusCode::PAYMENT_REQUIRED | StatusCode::TOO_MANY_REQUESTS => Some(
+            CreateReportRejection::QuotaExceeded(serde_json::from_str(body).unwrap_or_default()),
+        ),
+        _ => None,
+    }
+}
+
+fn parse_profiles_response(body: &str) -> Result<ProfilesResponse, Error> {
+    Ok(serde_json::from_str(body)?)
+}
+
+fn parse_tasks_response(body: &str) -> Result<TasksResponse, Error> {
+    Ok(serde_json::from_str(body)?)
+}
+
+/// Picks the enabled profile ids among the fetched profiles from a stored selection. Without
+/// a stored selection, the profiles the backend recommends are enabled, or every profile when the
+/// backend sends no recommendation at all. Profiles that appeared since the selection was stored
+/// are enabled only with `auto_enable_new`.
+fn reconcile_enabled_profiles(
+    profiles: &[Profile],
+    enabled_profile_ids: Option<&HashSet<i64>>,
+    known_profile_ids: Option<&HashSet<i64>>,
+    auto_enable_new: bool,
+) -> HashSet<i64> {
+    let has_recommendations = profiles.iter().any(|x| x.recommended.is_some());
+
+    profiles
+        .iter()
+        .filter(|x| match enabled_profile_ids {
+            Some(ids) => {
+                let is_new = known_profile_ids.is_some_and(|known| !known.contains(&x.id));
+                ids.contains(&x.id) || (is_new && auto_enable_new)
+            }
+            None if has_recommendations => x.recommended == Some(true),
+            None => true,
+        })
+        .map(|x| x.id)
+        .collect()
+}
+
+/// Keeps the persisted last results of the fetched profiles only, so removed profiles do not
+/// linger in settings and new ones start without a result.
+fn reconcile_last_results(
+    profiles: &[Profile],
+    last_results: &HashMap<i64, String>,
+) -> HashMap<i64, String> {
+    profiles
+        .iter()
+        .filter_map(|x| {
+            last_results
+                .get(&x.id)
+                .map(|status| (x.id, status.to_owned()))
+        })
+        .collect()
+}
+
+/// Sort rank of a task status, most important first: detections, then failures, then clean
+/// results, then tasks still running and statuses this client does not know.
+fn severity_rank(status: &str) -> u8 {
+    match status {
+        "detected" => 0,
+        "error" | "timeout" => 1,
+        "clean" => 2,
+        "new" | "pending" => 3,
+        _ => 4,
+    }
+}
+
+/// Human readable label for a final task status, without the detection name.
+fn verdict_label(status: &str) -> &'static str {
+    match status {
+        "clean" => "Clean",
+        "detected" => "Detected",
+        "timeout" => "Timeout",
+        "error" => "Error",
+        _ => "",
+    }
+}
+
+/// Machine names shared by more than one profile, which the backend should not send.
+fn duplicate_machine_names(profiles: &[Profile]) -> Vec<&str> {
+    let mut seen = HashSet::new();
+    let mut duplicates: Vec<&str> = profiles
+        .iter()
+        .map(|x| x.machine_name.as_str())
+        .filter(|x| !seen.insert(*x))
+        .collect();
+    duplicates.sort_unstable();
+    duplicates.dedup();
+    duplicates
+}
+
+/// Sorts profiles by the user's custom order. Profiles missing from it, such as ones added on the
+/// backend since the order was saved, keep their fetched order at the end.
+fn order_profiles<'a>(profiles: &'a [Profile], order: &[i64]) -> Vec<&'a Profile> {
+    let mut ordered: Vec<&Profile> = order
+        .iter()
+        .filter_map(|id| profiles.iter().find(|x| x.id == *id))
+        .collect();
+
+    ordered.extend(profiles.iter().filter(|x| !order.contains(&x.id)));
+    ordered
+}
+
+/// Filters reports by a case-insensitive `file_multihash` substring and an inclusive range of
+/// creation dates.
+fn filter_reports<'a>(
+    reports: &'a [Report],
+    hash_query: &str,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+) -> Vec<&'a Report> {
+    let hash_query = hash_query.trim().to_lowercase();
+
+    reports
+        .iter()
+        .filter(|x| x.file_multihash.to_lowercase().contains(&hash_query))
+        .filter(|x| from.is_none_or(|from| x.created_when.naive_utc().date() >= from))
+        .filter(|x| to.is_none_or(|to| x.created_when.naive_utc().date() <= to))
+        .collect()
+}
+
+fn build_info() -> String {
+    let built_when = BUILD_TIMESTAMP
+        .parse()
+        .ok()
+        .and_then(|x| Utc.timestamp_opt(x, 0).single());
+
+    match built_when {
+        Some(built_when) => format!(
+            "v{} (built {})",
+            VERSION,
+            built_when.format("%Y-%m-%d %H:%M UTC")
+        ),
+        None => format!("v{}", VERSION),
+    }
+}
+
+/// Renders the session log as CSV with an RFC 3339 timestamp column.
+fn audit_log_to_csv(entries: &[AuditEntry]) -> String {
+    let mut csv = "timestamp,action\n".to_owned();
+    for entry in entries {
+        csv.push_str(&format!(
+            "{},\"{}\"\n",
+            entry.timestamp.to_rfc3339(),
+            entry.action.replace('"', "\"\"")
+        ));
+    }
+    csv
+}
+
+fn task_status_label(status: &str) -> &'static str {
+    match status {
+        "new" => "waiting for worker",
+        "pending" => "processing",
+        "clean" => "clean",
+        "detected" => "detected",
+        "timeout" => "timeout",
+        "error" => "error",
+        _ => "unknown",
+    }
+}
+
+/// Zips the original file together with the task verdicts as `tasks.json`. Path separators in
+/// the file name are replaced so the archive cannot write outside the folder it is extracted to.
+/// Entries are stored uncompressed, which every archiver reads and keeps the wasm build small.
+fn build_bundle(file_name: &str, file: &[u8], tasks: &[Task]) -> Result<Vec<u8>, Error> {
+    let file_name = file_name.replace(['/', '\\'], "_");
+    let tasks = serde_json::to_vec_pretty(tasks)?;
+    let options = FileOptions::default().compression_method(CompressionMethod::Stored);
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    zip.start_file(file_name, options)?;
+    zip.write_all(file)?;
+    zip.start_file("tasks.json", options)?;
+    zip.write_all(&tasks)?;
+    Ok(zip.finish()?.into_inner())
+}
+
+/// How long ago `since` was, for display. Times slightly in the future, as seen when the client
+/// clock is behind the server's, read "just now" instead of a negative duration.
+fn format_elapsed(since: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let seconds = now.signed_duration_since(since).num_seconds().max(0);
+
+    match seconds {
+        0..=4 => "just now".into(),
+        5..=59 => format!("{}s ago", seconds),
+        60..=3599 => format!("{}m ago", seconds / 60),
+        3600..=86399 => format!("{}h ago", seconds / 3600),
+        _ => format!("{}d ago", seconds / 86400),
+    }
+}
+
+fn format_definitions_age(days: i64) -> String {
+    match days {
+        0 => "today".into(),
+        1 => "1 day old".into(),
+        _ => format!("{} days old", days),
+    }
+}
+
+fn summarize_tasks(tasks: &[Task]) -> String {
+    if tasks.is_empty() {
+        return "No engines ran".into();
+    }
+
+    let detected = tasks.iter().filter(|x| x.status == "detected").count();
+    let failed = tasks
+        .iter()
+        .filter(|x| x.status == "error" || x.status == "timeout")
+        .count();
+
+    match (detected, failed) {
+        (0, 0) => "Clean".into(),
+        (0, failed) => format!("{} failed", failed),
+        (detected, 0) => format!("{} detected", detected),
+        (detected, failed) => format!("{} detected, {} failed", detected, failed),
+    }
+}
+
+/// Detections and engine count of a scan, or `None` when no engine ran.
+fn scan_score(tasks: &[Task]) -> Option<(usize, usize)> {
+    if tasks.is_empty() {
+        return None;
+    }
+
+    let detected = tasks.iter().filter(|x| x.status == "detected").count();
+    Some((detected, tasks.len()))
+}
+
+/// Clean files are green, a few detections amber and anything above a tenth of engines red.
+fn scan_score_class(detected: usize, total: usize) -> &'static str {
+    if detected == 0 {
+        "notification is-success is-light has-text-centered"
+    } else if detected * 10 <= total {
+        "notification is-warning is-light has-text-centered"
+    } else {
+        "notification is-danger is-light has-text-centered"
+    }
+}
+
+/// Resolves an `api_url` relative to the page's base URI (`/api` against the origin, `api` against
+/// the base path), the way the browser would resolve a link. Absolute URLs are kept as they are.
+fn resolve_api_url(api_url: &str, base_uri: &str) -> String {
+    let api_url = api_url.trim();
+    if api_url.starts_with("http://") || api_url.starts_with("https://") || base_uri.is_empty() {
+        return api_url.to_owned();
+    }
+
+    let base_uri = base_uri.split(['?', '#']).next().unwrap_or(base_uri);
+    let scheme_end = base_uri.find("://").map_or(0, |x| x + 3);
+    let origin_end = base_uri[scheme_end..]
+        .find('/')
+        .map_or(base_uri.len(), |x| scheme_end + x);
+
+    if let Some(rest) = api_url.strip_prefix("//") {
+        format!("{}{}", &base_uri[..scheme_end], rest)
+    } else if api_url.starts_with('/') {
+        format!("{}{}", &base_uri[..origin_end], api_url)
+    } else {
+        let directory_end = base_uri[origin_end..]
+            .rfind('/')
+            .map_or(origin_end, |x| origin_end + x);
+        format!(
+            "{}/{}",
+            &base_uri[..directory_end],
+            api_url.trim_start_matches("./")
+        )
+    }
+}
+
+fn normalize_api_url(api_url: &str) -> Result<String, Error> {
+    let api_url = api_url.trim().trim_end_matches('/');
+
+    let host = api_url
+        .strip_prefix("https://")
+        .or_else(|| api_url.strip_prefix("http://"))
+        .ok_or_else(|| format_err!("Invalid api_url \"{}\": expected an http(s) URL", api_url))?;
+
+    if host.is_empty() || api_url.chars().any(char::is_whitespace) {
+        return Err(format_err!("Invalid api_url \"{}\"", api_url));
+    }
+
+    Ok(api_url.to_owned())
+}
+
+/// Checks that a hash looks like a hex-encoded multihash whose declared digest length matches the
+/// digest that follows, so obvious typos are caught before querying the backend.
+fn is_valid_multihash(hash: &str) -> bool {
+    if hash.len() < 4
+        || !hash.len().is_multiple_of(2)
+        || !hash.chars().all(|x| x.is_ascii_hexdigit())
+    {
+        return false;
+    }
+
+    match usize::from_str_radix(&hash[2..4], 16) {
+        Ok(digest_len) => hash.len() == 4 + digest_len * 2,
+        Err(_) => false,
+    }
+}
+
+/// Fills the `{query}` placeholder of a lookup URL template with the percent-encoded detection
+/// name. Templates that are not http(s) URLs or lack the placeholder are ignored, and the
+/// encoding keeps the query from adding path segments, parameters or fragments.
+fn threat_lookup_url(template: &str, query: &str) -> Option<String> {
+    if !is_valid_scan_url(template) || !template.contains("{query}") {
+        return None;
+    }
+
+    Some(template.replace("{query}", &percent_encode(query)))
+}
+
+/// Percent-encodes everything but RFC 3986 unreserved characters.
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|x| match x {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (x as char).to_string()
+            }
+            _ => format!("%{:02X}", x),
+        })
+        .collect()
+}
+
+/// The `profiles` query value: machine names separated by raw commas, each percent-encoded so a
+/// comma, `&` or `#` in a name stays inside that name.
+fn profiles_query_value<'a>(machine_names: impl IntoIterator<Item = &'a str>) -> String {
+    machine_names
+        .into_iter()
+        .map(percent_encode)
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+fn is_valid_scan_url(url: &str) -> bool {
+    let rest = match url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+    {
+        Some(rest) => rest,
+        None => return false,
+    };
+
+    let host = rest.split(&['/', '?', '#'][..]).next().unwrap();
+    !host.is_empty() && !url.chars().any(char::is_whitespace)
+}
+
+impl<A: Api> Renderable<Model<A>> for Model<A> {
+    fn view(&self) -> Html<Self> {
+        html! {
+            <>
+                { self.view_environment_banner() }
+                { self.view_storage_notice() }
+                { self.view_storage_write_warning() }
+                { self.view_scene() }
+                { self.view_connection_status() }
+                { self.view_idle_warning() }
+                { self.view_modal() }
+                { self.view_footer() }
+            </>
+        }
+    }
+}
+
+impl<A: Api> Model<A> {
+    fn view_footer(&self) -> Html<Self> {
+        if self.embed_mode {
+            return html! {};
+        }
+
+        html! {
+            <footer class="has-text-centered has-text-grey is-size-7"
+                style="position: fixed; bottom: 0.5em; width: 100%;">
+                { build_info() }
+                { self.view_copy_as_curl() }
+                { self.view_config_template_download() }
+            </footer>
+        }
+    }
+
+    fn view_scene(&self) -> Html<Self> {
         match self.scene {
             Scene::Loading => html! {
                 <section class="hero is-fullheight">
@@ -588,210 +4781,1886 @@ impl Renderable<Model> for Model {
                             <div class="columns is-centered is-vcentered is-mobile">
                                 <div class="column" style="max-width: 250px;">
                                     <progress class="progress is-medium is-dark" max="100" />
+                                    {
+                                        if let Some(error) = &self.fetch_profiles_error {
+                                            html! {
+                                                <p class="has-text-centered">
+                                                    <span class="icon has-text-danger">
+                                                        <i class="fas fa-info-circle"></i>
+                                                    </span>
+                                                    { error }
+                                                </p>
+                                            }
+                                        } else {
+                                            html! {}
+                                        }
+                                    }
+                                    {
+                                        if self.config.is_none() && self.config_fetch_attempts > 1 {
+                                            html! {
+                                                <p class="has-text-centered has-text-grey is-size-7">
+                                                    { format!("Connecting… (attempt {} of {})", self.config_fetch_attempts, CONFIG_FETCH_ATTEMPTS) }
+                                                </p>
+                                            }
+                                        } else {
+                                            html! {}
+                                        }
+                                    }
+                                </div>
+                            </div>
+                        </div>
+                    </div>
+                </section>
+            },
+            Scene::LoginRegister if self.auth_view == AuthView::PasswordReset => {
+                self.view_password_reset()
+            }
+            Scene::LoginRegister => html! {
+                <section class="hero is-fullheight">
+                    <div class="hero-body">
+                        <div class="container">
+                            <div class="columns is-centered is-vcentered is-mobile">
+                                <div class="column" style="max-width: 300px;">
+                                    // The notices sit in one wrapper that is always rendered, so the inputs
+                                    // below keep their place in the diff and are patched rather than
+                                    // recreated, which would drop focus and the caret while typing.
+                                    { self.view_login_notices() }
+                                    <div class="box is-centered">
+                                        <div class="field">
+                                            <div class="control has-icons-left">
+                                                <input id="login-username" class="input" type="text" placeholder="Username"
+                                                    autocomplete="username"
+                                                    value=&self.loginregister_form.username
+                                                    oninput=|e| Msg::LoginRegisterFormDataChange(LoginRegisterFormDataField::Username, e.value) />
+                                                <span class="icon is-small is-left">
+                                                    <i class="fas fa-user" />
+                                                </span>
+                                            </div>
+                                        </div>
+                                        <div class="field">
+                                            <div class="control has-icons-left">
+                                                <input id="login-password" class="input" type="password" placeholder="Password"
+                                                    autocomplete="current-password"
+                                                    oninput=|e| Msg::LoginRegisterFormDataChange(LoginRegisterFormDataField::Password, e.value) />
+                                                <span class="icon is-small is-left">
+                                                    <i class="fas fa-lock" />
+                                                </span>
+                                            </div>
+                                        </div>
+                                        <div class="field">
+                                            <label class="checkbox is-size-7">
+                                                <input type="checkbox"
+                                                    checked=self.settings.remember_username
+                                                    onchange=|_| Msg::ToggleRememberUsername />
+                                                { " Remember username" }
+                                            </label>
+                                        </div>
+                                        <div class="level is-mobile">
+                                            <div class="level-left">
+                                                <div class="level-item">
+                                                    <div class="field">
+                                                        <button class=if self.is_register_loading { "button is-loading" } else { "button" } type="button"
+                                                            disabled=self.is_register_disabled
+                                                            onclick=|_| Msg::Register>
+                                                            { "Register" }
+                                                        </button>
+                                                    </div>
+                                                </div>
+                                            </div>
+                                            <div class="level-right">
+                                                <div class="level-item">
+                                                    <div class="field">
+                                                        <button class=if self.is_login_loading { "button is-loading" } else { "button" } type="button"
+                                                            disabled=self.is_login_disabled
+                                                            onclick=|_| Msg::Login>
+                                                            { "Login" }
+                                                        </button>
+                                                    </div>
+                                                </div>
+                                            </div>
+                                        </div>
+                                        {
+                                            if self.feature_enabled("guest") {
+                                                html! {
+                                                    <div class="field">
+                                                        <button class=if self.is_guest_login_loading { "button is-text is-fullwidth is-loading" } else { "button is-text is-fullwidth" } type="button"
+                                                            disabled=self.is_login_disabled
+                                                            onclick=|_| Msg::GuestLogin>
+                                                            { "Continue as guest" }
+                                                        </button>
+                                                    </div>
+                                                }
+                                            } else {
+                                                html! {}
+                                            }
+                                        }
+                                        <p class="has-text-centered is-size-7">
+                                            <a href="#" onclick=|e| { e.prevent_default(); Msg::SetAuthView(AuthView::PasswordReset) }>
+                                                { "Forgot password?" }
+                                            </a>
+                                        </p>
+                                    </div>
+                                    { self.view_test_connection() }
+                                </div>
+                            </div>
+                        </div>
+                    </div>
+                </section>
+            },
+            Scene::History => self.view_history(),
+            Scene::FetchConfigError => html! {
+                <section class="hero is-fullheight">
+                    <div class="hero-body">
+                        <div class="container">
+                            <div class="columns is-centered is-vcentered is-mobile">
+                                <div class="column" style="max-width: 300px;">
+                                    { self.view_test_connection() }
+                                </div>
+                            </div>
+                        </div>
+                    </div>
+                </section>
+            },
+            Scene::Maintenance => self.view_maintenance(),
+            // Profiles are fetched before entering this scene, but render a way out rather than
+            // a blank page should they ever be missing.
+            Scene::LoggedIn if self.fetched_profiles.is_none() => self.view_error_fallback(),
+            Scene::LoggedIn => html! {
+                <section class=if self.embed_mode { "section" } else { "hero is-fullheight" }>
+                    <div class=if self.embed_mode { "" } else { "hero-body" }>
+                        <div class="container">
+                            <div class="columns is-centered is-vcentered is-mobile">
+                                <div class="column" style="max-width: 400px;">
+                                    { self.view_welcome_back() }
+                                    <nav class="panel">
+                                        <p class="panel-heading">
+                                            { "Profiles" }
+                                            { self.view_polling_indicator() }
+                                            { self.view_density_toggle() }
+                                        </p>
+                                        {
+                                            if self.is_mobile {
+                                                self.view_profile_cards()
+                                            } else {
+                                                self.view_profiles_table()
+                                            }
+                                        }
+                                        <label class="panel-block">
+                                            <input type="checkbox"
+                                                checked=self.show_detections_only
+                                                disabled=self.current_pending_tasks.is_none()
+                                                onchange=|_| Msg::ToggleDetectionsFilter />
+                                            { "Show only detections" }
+                                        </label>
+                                        <label class="panel-block">
+                                            <input type="checkbox"
+                                                checked=self.group_by_severity
+                                                disabled=self.current_pending_tasks.is_none()
+                                                onchange=|_| Msg::ToggleGroupBySeverity />
+                                            { "Group by verdict" }
+                                        </label>
+                                        <label class="panel-block">
+                                            <input type="checkbox"
+                                                checked=self.settings.collapse_clean
+                                                onchange=|_| Msg::ToggleCollapseClean />
+                                            { "Collapse clean results" }
+                                        </label>
+                                        {
+                                            if self.settings.muted_profile_ids.is_empty() {
+                                                html! {}
+                                            } else {
+                                                html! {
+                                                    <label class="panel-block">
+                                                        <input type="checkbox"
+                                                            checked=self.show_muted_profiles
+                                                            onchange=|_| Msg::ToggleShowMutedProfiles />
+                                                        { format!("Show muted ({})", self.settings.muted_profile_ids.len()) }
+                                                    </label>
+                                                }
+                                            }
+                                        }
+                                        <label class="panel-block">
+                                            <input type="checkbox"
+                                                checked=self.settings.auto_enable_new_profiles
+                                                onchange=|_| Msg::ToggleAutoEnableNewProfiles />
+                                            { "Enable new engines automatically" }
+                                        </label>
+                                        {
+                                            if self.feature_enabled("completion_notifications") {
+                                                html! {
+                                                    <label class="panel-block">
+                                                        <input type="checkbox"
+                                                            checked=self.settings.notify_on_completion
+                                                            onchange=|_| Msg::ToggleCompletionNotifications />
+                                                        { "Notify me when a scan completes" }
+                                                    </label>
+                                                }
+                                            } else {
+                                                html! {}
+                                            }
+                                        }
+                                        <div class="panel-block">
+                                            <button class="button is-small is-fullwidth" type="button"
+                                                onclick=|_| Msg::SelectAllProfiles>
+                                                { "Select all" }
+                                            </button>
+                                        </div>
+                                    </nav>
+
+                                    {
+                                        if let Some((enabled, total)) = self.partial_profile_selection() {
+                                            html! {
+                                                <p class="is-size-7 has-text-centered has-text-grey" style="margin-bottom: 1em;">
+                                                    { format!("Scanning with {} of {} engines — ", enabled, total) }
+                                                    <a href="#" onclick=|e| { e.prevent_default(); Msg::SelectAllProfiles }>
+                                                        { "reset to all" }
+                                                    </a>
+                                                </p>
+                                            }
+                                        } else {
+                                            html! {}
+                                        }
+                                    }
+
+                                    {
+                                        if self.current_pending_tasks.as_ref().map_or(false, |x| x.is_empty()) {
+                                            html! {
+                                                <p class="has-text-centered" style="margin-bottom: 1em;">
+                                                    <span class="icon has-text-warning">
+                                                        <i class="fas fa-info-circle"></i>
+                                                    </span>
+                                                    { "No engines ran for this report — check your profile selection" }
+                                                </p>
+                                            }
+                                        } else {
+                                            html! {}
+                                        }
+                                    }
+
+                                    {
+                                        if self.timed_out_task_ids.is_empty() {
+                                            html! {}
+                                        } else {
+                                            html! {
+                                                <div class="has-text-centered" style="margin-bottom: 1em;">
+                                                    <button class="button is-small" type="button"
+                                                        onclick=|_| Msg::KeepWaiting>
+                                                        { "Keep waiting" }
+                                                    </button>
+                                                </div>
+                                            }
+                                        }
+                                    }
+
+                                    <div class="is-sr-only" aria-live="polite">
+                                        { &self.scan_announcement }
+                                    </div>
+
+                                    {
+                                        if self.is_report_unavailable {
+                                            html! {
+                                                <p class="has-text-centered" style="margin-bottom: 1em;">
+                                                    <span class="icon has-text-warning">
+                                                        <i class="fas fa-info-circle"></i>
+                                                    </span>
+                                                    { "This scan is no longer available" }
+                                                </p>
+                                            }
+                                        } else {
+                                            html! {}
+                                        }
+                                    }
+
+                                    {
+                                        match self.large_selection_warning() {
+                                            Some(warning) if !self.is_large_selection_acknowledged => html! {
+                                                <div class="notification is-warning">
+                                                    <p>{ warning }</p>
+                                                    <button class="button is-small" type="button" style="margin-top: 0.5em;"
+                                                        onclick=|_| Msg::AcknowledgeLargeSelection>
+                                                        { "Proceed anyway" }
+                                                    </button>
+                                                </div>
+                                            },
+                                            _ => html! {},
+                                        }
+                                    }
+
+                                    {
+                                        if self.is_scan_complete() && !self.is_file_uploading {
+                                            html! {
+                                                <div class="has-text-centered" style="margin-bottom: 1em;">
+                                                    <button class="button is-primary" type="button"
+                                                        onclick=|_| Msg::ScanAnotherSameProfiles>
+                                                        <span class="icon">
+                                                            <i class="fas fa-redo"></i>
+                                                        </span>
+                                                        <span>{ "Scan another file (same engines)" }</span>
+                                                    </button>
+                                                    {
+                                                        if cfg!(feature = "pdf_export") && self.feature_enabled("pdf_export") {
+                                                            html! {
+                                                                <button class="button" type="button" style="margin-left: 0.5em;"
+                                                                    onclick=|_| Msg::DownloadPdf>
+                                                                    <span class="icon">
+                                                                        <i class="fas fa-file-pdf"></i>
+                                                                    </span>
+                                                                    <span>{ "Download PDF" }</span>
+                                                                </button>
+                                                            }
+                                                        } else {
+                                                            html! {}
+                                                        }
+                                                    }
+                                                    {
+                                                        if self.feature_enabled("bundle_export") && self.current_bundle_file().is_some() {
+                                                            html! {
+                                                                <button class="button" type="button" style="margin-left: 0.5em;"
+                                                                    onclick=|_| Msg::DownloadBundle>
+                                                                    <span class="icon">
+                                                                        <i class="fas fa-file-archive"></i>
+                                                                    </span>
+                                                                    <span>{ "Download bundle" }</span>
+                                                                </button>
+                                                            }
+                                                        } else {
+                                                            html! {}
+                                                        }
+                                                    }
+                                                </div>
+                                            }
+                                        } else {
+                                            html! {}
+                                        }
+                                    }
+
+                                    { self.view_report_tabs() }
+                                    { self.view_report_id() }
+                                    { self.view_scan_phase() }
+                                    { self.view_scan_score() }
+                                    { self.view_jump_to_detection() }
+                                    { self.view_retry_failed() }
+                                    { self.view_check_status() }
+                                    { self.view_clear_results() }
+                                    { self.view_previous_report_toggle() }
+
+                                    <div class="file is-boxed is-centered">
+                                        <label class="file-label">
+                                            {
+                                                if self.is_file_uploading || !self.config_override_errors.is_empty() {
+                                                    html! { <input id="file-input" class="file-input" type="file" disabled=true onchange=|e| Msg::LoadFile(e) /> }
+                                                } else {
+                                                    html! { <input id="file-input" class="file-input" type="file" onchange=|e| Msg::LoadFile(e) /> }
+                                                }
+                                            }
+                                            <span class="file-cta">
+                                                <span class="file-icon">
+                                                    <i class="fas fa-upload"></i>
+                                                </span>
+                                                <span class="file-label">
+                                                    { "Drag to scan" }
+                                                </span>
+                                            </span>
+                                        </label>
+                                    </div>
+                                    { self.view_upload_limit() }
+
+                                    {
+                                        if self.scan_phase() == Some(ScanPhase::Reading) {
+                                            html! {
+                                                <div class="has-text-centered" style="margin-top: 0.5em;">
+                                                    <button class="button is-small is-light" onclick=|_| Msg::CancelFileRead>
+                                                        { "Cancel reading" }
+                                                    </button>
+                                                </div>
+                                            }
+                                        } else {
+                                            html! {}
+                                        }
+                                    }
+
+                                    {
+                                        if self.feature_enabled("url_scanning") {
+                                            html! {
+                                                <div class="field has-addons" style="margin-top: 1em;">
+                                                    <div class="control is-expanded has-icons-left">
+                                                        <input class="input" type="url" placeholder="https://example.com/file"
+                                                            oninput=|e| Msg::ScanUrlChange(e.value) />
+                                                        <span class="icon is-small is-left">
+                                                            <i class="fas fa-link" />
+                                                        </span>
+                                                    </div>
+                                                    <div class="control">
+                                                        <button class=if self.is_url_submitting { "button is-loading" } else { "button" } type="button"
+                                                            disabled=self.is_url_submitting || self.is_file_uploading || !self.config_override_errors.is_empty()
+                                                            onclick=|_| Msg::ScanUrl>
+                                                            { "Scan URL" }
+                                                        </button>
+                                                    </div>
+                                                </div>
+                                            }
+                                        } else {
+                                            html! {}
+                                        }
+                                    }
+
+                                    {
+                                        if let Some(error) = &self.create_report_error {
+                                            html! {
+                                                <p class="has-text-centered" style="margin-top: 1em;">
+                                                    <span class="icon has-text-danger">
+                                                        <i class="fas fa-info-circle"></i>
+                                                    </span>
+                                                    { error }
+                                                </p>
+                                            }
+                                        } else {
+                                            html! {}
+                                        }
+                                    }
+
+                                    { self.view_create_report_rejection() }
+
+                                    { self.view_hash_lookup() }
+
+                                    {
+                                        if self.is_guest || self.session_reports.is_empty() {
+                                            html! {}
+                                        } else {
+                                            html! {
+                                                <nav class="panel" style="margin-top: 2em;">
+                                                    <p class="panel-heading">
+                                                        { "This session" }
+                                                    </p>
+                                                    {
+                                                        for self.session_reports.iter().rev().map(|session_report| {
+                                                            let report_id = session_report.report_id;
+                                                            html! {
+                                                                <a class="panel-block" href="#" onclick=|e| { e.prevent_default(); Msg::ShowSessionReport(report_id) }>
+                                                                    <span class="panel-icon">
+                                                                        <i class="fas fa-file"></i>
+                                                                    </span>
+                                                                    { &session_report.file_name }
+                                                                    <span class="has-text-grey" style="margin-left: auto;">
+                                                                        { session_report.summary.as_ref().map_or("Scanning..", |x| x.as_str()) }
+                                                                    </span>
+                                                                </a>
+                                                            }
+                                                        })
+                                                    }
+                                                </nav>
+                                            }
+                                        }
+                                    }
+
+                                    { self.view_account_actions() }
                                 </div>
                             </div>
                         </div>
                     </div>
-                </section>
-            },
-            Scene::LoginRegister => html! {
-                <section class="hero is-fullheight">
-                    <div class="hero-body">
-                        <div class="container">
-                            <div class="columns is-centered is-vcentered is-mobile">
-                                <div class="column" style="max-width: 300px;">
-                                    {
-                                        if let Some(error) = &self.loginregister_error {
-                                            html! {
-                                                <p class="has-text-centered" style="margin-top: 1em; margin-bottom: 1em;">
-                                                    <span class="icon has-text-danger">
-                                                        <i class="fas fa-info-circle"></i>
-                                                    </span>
-                                                    { error }
-                                                </p>
-                                            }
-                                        } else {
-                                            html! {
-                                                <p class="has-text-centered" style="margin-top: 1em; margin-bottom: 1em;">
-                                                    <span class="icon has-text-info">
-                                                        <i class="fas fa-info-circle"></i>
-                                                    </span>
-                                                    { "Fill the form below" }
-                                                </p>
-                                            }
-                                        }
-                                    }
-                                    <div class="box is-centered">
-                                        <div class="field">
-                                            <div class="control has-icons-left">
-                                                <input class="input" type="text" placeholder="Username"
-                                                    oninput=|e| Msg::LoginRegisterFormDataChange(LoginRegisterFormDataField::Username, e.value) />
-                                                <span class="icon is-small is-left">
-                                                    <i class="fas fa-user" />
-                                                </span>
-                                            </div>
+                </section>
+            },
+        }
+    }
+
+    fn view_density_toggle(&self) -> Html<Self> {
+        if self.embed_mode {
+            return html! {};
+        }
+
+        html! {
+            <button class="button is-small is-pulled-right" type="button"
+                title=if self.settings.compact_table { "Comfortable view" } else { "Compact view" }
+                onclick=|_| Msg::ToggleDensity>
+                <span class="icon is-small">
+                    <i class=if self.settings.compact_table { "fas fa-expand" } else { "fas fa-compress" }></i>
+                </span>
+            </button>
+        }
+    }
+
+    /// History and logout; the host page owns the session when embedded.
+    fn view_account_actions(&self) -> Html<Self> {
+        if self.embed_mode {
+            return html! {};
+        }
+
+        html! {
+            <div class="has-text-centered" style="margin-top: 2em; margin-bottom: 2em;">
+                {
+                    if self.feature_enabled("history") && !self.is_guest {
+                        html! {
+                            <button class="button" type="button" style="margin-right: 0.5em;"
+                                onclick=|_| Msg::ShowHistory>
+                                { "History" }
+                            </button>
+                        }
+                    } else {
+                        html! {}
+                    }
+                }
+                <button class=format!("button {} {}",
+                    if self.is_logout_loading || self.is_logout_everywhere_loading { "is-loading" } else {""},
+                    if self.logout_everywhere_error.is_some() {"is-danger"} else {""}),
+                    type="button"
+                    disabled=self.is_logout_disabled
+                    onclick=|_| Msg::ConfirmLogout>
+                    { "Logout" }
+                </button>
+                {
+                    if let Some(error) = &self.logout_everywhere_error {
+                        html! {
+                            <p class="help is-danger">{ error }</p>
+                        }
+                    } else {
+                        html! {}
+                    }
+                }
+            </div>
+        }
+    }
+
+    fn visible_profiles(&self) -> Vec<&Profile> {
+        let mut profiles: Vec<&Profile> = self
+            .fetched_profiles
+            .iter()
+            .flat_map(|x| order_profiles(&x.profiles, &self.settings.profile_order))
+            .filter(|x| self.is_profile_visible(x))
+            .collect();
+
+        if let (true, Some(tasks)) = (self.group_by_severity, self.displayed_tasks()) {
+            profiles.sort_by_cached_key(|profile| {
+                let rank = tasks
+                    .iter()
+                    .find(|x| x.profile_id == profile.id)
+                    .map_or(u8::MAX, |x| severity_rank(&x.status));
+                (rank, profile.human_name.to_lowercase())
+            });
+        }
+
+        profiles
+    }
+
+    /// With `collapse_clean`, leaves out profiles whose task came back clean unless the user
+    /// expanded them, and returns how many are clean so a summary line can stand in for them.
+    fn fold_clean_profiles<'a>(&self, profiles: Vec<&'a Profile>) -> (Vec<&'a Profile>, usize) {
+        let tasks = match self.displayed_tasks() {
+            Some(tasks) if self.settings.collapse_clean => tasks,
+            _ => return (profiles, 0),
+        };
+
+        let clean_profile_ids: HashSet<i64> = tasks
+            .iter()
+            .filter(|x| x.status == "clean")
+            .map(|x| x.profile_id)
+            .collect();
+        let clean_count = profiles
+            .iter()
+            .filter(|x| clean_profile_ids.contains(&x.id))
+            .count();
+
+        if self.is_clean_expanded {
+            return (profiles, clean_count);
+        }
+
+        let profiles = profiles
+            .into_iter()
+            .filter(|x| !clean_profile_ids.contains(&x.id))
+            .collect();
+        (profiles, clean_count)
+    }
+
+    fn view_clean_summary(&self, clean_count: usize) -> Html<Self> {
+        html! {
+            <span class="has-text-grey">
+                { format!("{} engines clean ", clean_count) }
+                <a href="#" onclick=|e| { e.prevent_default(); Msg::ToggleCleanExpanded }>
+                    { if self.is_clean_expanded { "(hide)" } else { "(show)" } }
+                </a>
+            </span>
+        }
+    }
+
+    fn view_profiles_table(&self) -> Html<Self> {
+        let (profiles, clean_count) = self.fold_clean_profiles(self.visible_profiles());
+
+        html! {
+            <table class=if self.settings.compact_table {
+                "table is-bordered is-striped is-narrow is-hoverable is-fullwidth"
+            } else {
+                "table is-bordered is-striped is-hoverable is-fullwidth"
+            }>
+                <thead>
+                    <tr>
+                        <th>{ "Engine" }</th>
+                        <th>{ "Status" }</th>
+                        <th>{ "Last result" }</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    { for profiles.into_iter().map(|profile| self.view_profile_row(profile)) }
+                    {
+                        if clean_count > 0 {
+                            html! {
+                                <tr>
+                                    <td colspan="3">{ self.view_clean_summary(clean_count) }</td>
+                                </tr>
+                            }
+                        } else {
+                            html! {}
+                        }
+                    }
+                </tbody>
+            </table>
+        }
+    }
+
+    /// Profiles whose task was detected, in the order they are displayed.
+    fn detected_profile_ids(&self) -> Vec<i64> {
+        let tasks = match self.displayed_tasks() {
+            Some(tasks) => tasks,
+            None => return Vec::new(),
+        };
+
+        self.fold_clean_profiles(self.visible_profiles())
+            .0
+            .into_iter()
+            .filter(|profile| {
+                tasks
+                    .iter()
+                    .any(|x| x.profile_id == profile.id && x.status == "detected")
+            })
+            .map(|x| x.id)
+            .collect()
+    }
+
+    /// Narrow screens get one stacked block per profile instead of the table.
+    fn view_profile_cards(&self) -> Html<Self> {
+        let (profiles, clean_count) = self.fold_clean_profiles(self.visible_profiles());
+
+        html! {
+            <>
+                {
+                    for profiles.into_iter().map(|profile| {
+                        let profile_id = profile.id;
+                        let is_muted = self.settings.muted_profile_ids.contains(&profile.id);
+                        let is_applicable = is_module_applicable(&profile.module, self.scan_input_kind);
+                        html! {
+                            <div id=format!("profile-{}", profile_id) tabindex="-1"
+                                class=if self.highlighted_profile_id == Some(profile_id) { "panel-block is-active" } else { "panel-block" }
+                                style="display: block;">
+                                <label class=if is_muted || !is_applicable { "checkbox has-text-grey" } else { "checkbox" }>
+                                    <input
+                                        type="checkbox"
+                                        checked=self.enabled_profiles.contains(&profile.id) && is_applicable
+                                        disabled=is_muted || !is_applicable
+                                        onchange=|_| Msg::ToggleProfile(profile_id)
+                                    />
+                                    { " " }
+                                    <strong>{ &profile.human_name }</strong>
+                                </label>
+                                <a class="is-size-7" style="margin-left: 0.5em;" href="#"
+                                    onclick=|e| { e.prevent_default(); Msg::ToggleProfileMute(profile_id) }>
+                                    { if is_muted { "unmute" } else { "mute" } }
+                                </a>
+                                { self.view_definitions_age(profile) }
+                                <p class="is-size-7">{ self.view_task_status_text(profile) }</p>
+                                <p class="is-size-7">{ "Last result: " }{ self.view_last_result(profile) }</p>
+                            </div>
+                        }
+                    })
+                }
+                {
+                    if clean_count > 0 {
+                        html! {
+                            <div class="panel-block">{ self.view_clean_summary(clean_count) }</div>
+                        }
+                    } else {
+                        html! {}
+                    }
+                }
+            </>
+        }
+    }
+
+    fn view_profile_row(&self, profile: &Profile) -> Html<Self> {
+        let is_changed = match (&self.previous_verdicts, &self.current_pending_tasks) {
+            (Some(previous), Some(tasks)) => {
+                changed_verdicts(previous, tasks).contains(&profile.id)
+            }
+            _ => false,
+        };
+
+        let profile_id = profile.id;
+        let is_muted = self.settings.muted_profile_ids.contains(&profile.id);
+        let is_applicable = is_module_applicable(&profile.module, self.scan_input_kind);
+
+        html! {
+            <tr id=format!("profile-{}", profile_id) draggable="true" tabindex="0"
+                class=if self.highlighted_profile_id == Some(profile_id) { "is-selected" } else { "" }
+                title="Drag to reorder, press Space to toggle"
+                ondragstart=|e| {
+                    if let Some(data_transfer) = e.data_transfer() {
+                        data_transfer.set_data("text/plain", "");
+                    }
+                    Msg::DragProfileStart(profile_id)
+                }
+                ondragover=|e| { e.prevent_default(); Msg::NoOp }
+                ondrop=|e| { e.prevent_default(); Msg::DropProfile(profile_id) }
+                onkeydown=|e| {
+                    // Keys pressed on the checkbox or a link inside the row are theirs to handle.
+                    if is_profile_row_toggle_key(&e.key(), e.target() == e.current_target()) {
+                        e.prevent_default();
+                        Msg::ToggleProfile(profile_id)
+                    } else {
+                        Msg::NoOp
+                    }
+                }>
+                <td class=if is_muted || !is_applicable { "has-text-grey" } else { "" }
+                    title=if is_applicable { "" } else { "Does not apply to this kind of scan" }>
+                    <input
+                        type="checkbox"
+                        checked=self.enabled_profiles.contains(&profile.id) && is_applicable
+                        disabled=is_muted || !is_applicable
+                        onchange=|_| Msg::ToggleProfile(profile_id)
+                    />
+                    { &profile.human_name }
+                    {
+                        html! {
+                            <a class="is-size-7" style="margin-left: 0.5em;" href="#"
+                                title=if is_muted { "Include this engine in scans again" } else { "Exclude this engine from all scans" }
+                                onclick=|e| { e.prevent_default(); Msg::ToggleProfileMute(profile_id) }>
+                                { if is_muted { "unmute" } else { "mute" } }
+                            </a>
+                        }
+                    }
+                    {
+                        html! {
+                            <a class="is-size-7" style="margin-left: 0.5em;" href="#"
+                                onclick=|e| { e.prevent_default(); Msg::ToggleProfileConfig(profile_id) }>
+                                { if self.expanded_profile_configs.contains(&profile.id) { "hide config" } else { "config" } }
+                            </a>
+                        }
+                    }
+                    {
+                        if self.config_overrides.contains_key(&profile.machine_name) {
+                            html! {
+                                <span class="tag is-info is-light" style="margin-left: 0.5em;">{ "override" }</span>
+                            }
+                        } else {
+                            html! {}
+                        }
+                    }
+                    {
+                        if is_changed {
+                            html! {
+                                <span class="tag is-warning" style="margin-left: 0.5em;"
+                                    title="Verdict changed since the previous scan of this file">
+                                    { "changed" }
+                                </span>
+                            }
+                        } else {
+                            html! {}
+                        }
+                    }
+                    { self.view_definitions_age(profile) }
+                    { self.view_profile_config(profile) }
+                </td>
+                { self.view_task_status(profile) }
+                <td>{ self.view_last_result(profile) }</td>
+            </tr>
+        }
+    }
+
+    /// How old the engine's definitions are, highlighted past `stale_definitions_days`.
+    fn view_definitions_age(&self, profile: &Profile) -> Html<Self> {
+        let definitions_date = match profile.definitions_date {
+            Some(definitions_date) => definitions_date,
+            None => return html! {},
+        };
+
+        let days = Utc::now()
+            .signed_duration_since(definitions_date)
+            .num_days()
+            .max(0);
+        let stale_after = self
+            .config
+            .as_ref()
+            .and_then(|x| x.stale_definitions_days)
+            .unwrap_or(DEFAULT_STALE_DEFINITIONS_DAYS);
+        let is_stale = days > stale_after;
+
+        html! {
+            <span class=if is_stale { "tag is-warning" } else { "tag is-light" } style="margin-left: 0.5em;"
+                title=format!("Definitions updated {}", definitions_date.format("%Y-%m-%d %H:%M UTC"))>
+                { format!("Defs: {}", format_definitions_age(days)) }
+            </span>
+        }
+    }
+
+    fn view_profile_config(&self, profile: &Profile) -> Html<Self> {
+        if !self.expanded_profile_configs.contains(&profile.id) {
+            return html! {};
+        }
+
+        let machine_name = profile.machine_name.to_owned();
+        let draft = self
+            .config_override_drafts
+            .get(&profile.machine_name)
+            .map_or("", String::as_str);
+        let error = self.config_override_errors.get(&profile.machine_name);
+
+        html! {
+            <div>
+                { self.view_profile_config_value(profile) }
+                <div class="field" style="margin-top: 0.5em;">
+                    <label class="label is-small">{ "Override config for the next scan" }</label>
+                    <div class="control">
+                        <textarea class=if error.is_some() { "textarea is-small is-danger" } else { "textarea is-small" }
+                            rows=3
+                            placeholder="{}"
+                            value=draft
+                            oninput=|e| Msg::ConfigOverrideChange(machine_name.to_owned(), e.value) />
+                    </div>
+                    {
+                        if let Some(error) = error {
+                            html! { <p class="help is-danger">{ format!("Invalid JSON: {}", error) }</p> }
+                        } else {
+                            html! {}
+                        }
+                    }
+                </div>
+            </div>
+        }
+    }
+
+    fn view_profile_config_value(&self, profile: &Profile) -> Html<Self> {
+        let config = match &profile.config {
+            Some(config) => config,
+            None => return html! {},
+        };
+
+        let profile_id = profile.id;
+        let pretty = serde_json::to_string_pretty(config).unwrap_or_default();
+        let preview = if self.full_profile_configs.contains(&profile.id) {
+            None
+        } else {
+            truncate_pretty_json(&pretty, PROFILE_CONFIG_PREVIEW_LEN)
+        };
+
+        html! {
+            <div style="margin-top: 0.5em;">
+                <pre class="is-size-7" style="max-height: 20em; overflow: auto;">
+                    { preview.unwrap_or(&pretty) }
+                    { if preview.is_some() { "\n…" } else { "" } }
+                </pre>
+                <div class="buttons" style="margin-top: 0.25em;">
+                    {
+                        if preview.is_some() {
+                            html! {
+                                <button class="button is-small" type="button"
+                                    onclick=|_| Msg::ShowFullProfileConfig(profile_id)>
+                                    { "Show full config" }
+                                </button>
+                            }
+                        } else {
+                            html! {}
+                        }
+                    }
+                    <button class="button is-small" type="button"
+                        onclick=|_| Msg::CopyProfileConfig(profile_id)>
+                        { "Copy raw" }
+                    </button>
+                </div>
+            </div>
+        }
+    }
+
+    fn view_task_status(&self, profile: &Profile) -> Html<Self> {
+        html! {
+            <td>{ self.view_task_status_text(profile) }</td>
+        }
+    }
+
+    /// The verdict persisted from an earlier scan is greyed until the current scan has its own.
+    fn view_last_result(&self, profile: &Profile) -> Html<Self> {
+        let status = match self.settings.last_results.get(&profile.id) {
+            Some(status) => status,
+            None => return html! { { "-" } },
+        };
+
+        let is_current = self.current_pending_tasks.as_ref().is_some_and(|tasks| {
+            tasks
+                .iter()
+                .any(|x| x.profile_id == profile.id && x.status != "new" && x.status != "pending")
+        });
+
+        html! {
+            <span class=if is_current { "" } else { "has-text-grey" }>
+                { verdict_label(status) }
+            </span>
+        }
+    }
+
+    fn view_task_status_text(&self, profile: &Profile) -> Html<Self> {
+        let tasks = match self.displayed_tasks() {
+            Some(tasks) => tasks,
+            None => return html! { { "Idle" } },
+        };
+
+        let task = match tasks.iter().find(|x| x.profile_id == profile.id) {
+            Some(task) => task,
+            None => return html! {},
+        };
+
+        if self.timed_out_task_ids.contains(&task.id) {
+            return html! {
+                <span class="has-text-grey is-italic">{ "Timed out waiting" }</span>
+            };
+        }
+
+        if let ("new", Some(queue_position)) = (task.status.as_str(), task.queue_position) {
+            return html! { { format!("Queued (#{})", queue_position) } };
+        }
+
+        let lookup_url = match (task.status.as_str(), &task.message, &self.config) {
+            ("detected", Some(message), Some(config)) => config
+                .threat_lookup_url_template
+                .as_ref()
+                .and_then(|template| threat_lookup_url(template, message)),
+            _ => None,
+        };
+
+        if let (Some(lookup_url), Some(message)) = (lookup_url, &task.message) {
+            return html! {
+                <a href=lookup_url target="_blank" rel="noopener noreferrer"
+                    title="Look up this detection">
+                    { message }
+                </a>
+            };
+        }
+
+        if task.status == "error" {
+            return self.view_task_error(profile.id, task);
+        }
+
+        html! {
+            {
+                match task.status.as_str() {
+                    "new" => "Waiting for worker..",
+                    "pending" => "Processing..",
+                    "clean" => "Clean",
+                    "detected" => task.message.as_ref().map_or("Detected", String::as_str),
+                    "timeout" => "Timeout",
+                    _ => ""
+                }
+            }
+        }
+    }
+
+    /// Error rows carry the server's reason and a retry for just that engine. Retrying is only
+    /// offered for the report being polled, not for a previous one being looked at.
+    fn view_task_error(&self, profile_id: i64, task: &Task) -> Html<Self> {
+        let message = task.message.as_deref().map(str::trim).unwrap_or("");
+        let can_retry = !self.is_viewing_previous_report && self.current_report_id.is_some();
+
+        html! {
+            <div>
+                <span class="has-text-danger">{ "Error" }</span>
+                {
+                    if can_retry {
+                        html! {
+                            <button class="button is-small is-light" type="button" style="margin-left: 0.5em;"
+                                disabled=self.is_retry_failed_loading
+                                onclick=|_| Msg::RetryTask(profile_id)>
+                                { "Retry" }
+                            </button>
+                        }
+                    } else {
+                        html! {}
+                    }
+                }
+                <p class="is-size-7 has-text-grey">
+                    { if message.is_empty() { "No details were given" } else { message } }
+                </p>
+            </div>
+        }
+    }
+
+    fn view_environment_banner(&self) -> Html<Self> {
+        let environment = match self
+            .config
+            .as_ref()
+            .and_then(Config::non_production_environment)
+        {
+            Some(environment) => environment,
+            None => return html! {},
+        };
+
+        html! {
+            <div class="has-background-warning has-text-centered has-text-weight-bold is-size-7" role="note"
+                style="position: fixed; top: 0; width: 100%; z-index: 40; padding: 0.25em;">
+                { format!("{} — not for real scans", environment.to_uppercase()) }
+            </div>
+        }
+    }
+
+    fn view_login_notices(&self) -> Html<Self> {
+        html! {
+            <div>
+                {
+                    if let Some(warning) = &self.logout_warning {
+                        html! {
+                            <p class="has-text-centered is-size-7" style="margin-bottom: 1em;">
+                                <span class="icon has-text-warning">
+                                    <i class="fas fa-exclamation-triangle"></i>
+                                </span>
+                                { warning }
+                            </p>
+                        }
+                    } else {
+                        html! {}
+                    }
+                }
+                {
+                    if let Some(error) = &self.loginregister_error {
+                        html! {
+                            <p class="has-text-centered" style="margin-top: 1em; margin-bottom: 1em;">
+                                <span class="icon has-text-danger">
+                                    <i class="fas fa-info-circle"></i>
+                                </span>
+                                { error }
+                            </p>
+                        }
+                    } else if self.settings.login_hint_dismissed {
+                        html! {}
+                    } else {
+                        html! {
+                            <p class="has-text-centered" style="margin-top: 1em; margin-bottom: 1em;">
+                                <span class="icon has-text-info">
+                                    <i class="fas fa-info-circle"></i>
+                                </span>
+                                { "Fill the form below" }
+                                <button class="delete is-small" type="button" aria-label="Dismiss"
+                                    style="margin-left: 0.5em; vertical-align: middle;"
+                                    onclick=|_| Msg::DismissLoginHint />
+                            </p>
+                        }
+                    }
+                }
+            </div>
+        }
+    }
+
+    fn view_welcome_back(&self) -> Html<Self> {
+        if self.welcome_back_tt.is_none() {
+            return html! {};
+        }
+
+        html! {
+            <div class="notification is-info is-light is-size-7" role="status">
+                <button class="delete" type="button" aria-label="Dismiss"
+                    onclick=|_| Msg::HideWelcomeBack></button>
+                { "Welcome back — you are still signed in from your previous visit." }
+            </div>
+        }
+    }
+
+    fn view_storage_write_warning(&self) -> Html<Self> {
+        let warning = match &self.storage_write_warning {
+            Some(warning) => warning,
+            None => return html! {},
+        };
+
+        html! {
+            <div class="notification is-warning is-light has-text-centered is-size-7" role="alert"
+                style="position: fixed; bottom: 2.5em; left: 50%; transform: translateX(-50%); z-index: 30;">
+                <button class="delete" type="button" aria-label="Dismiss"
+                    onclick=|_| Msg::DismissStorageWriteWarning></button>
+                { warning }
+            </div>
+        }
+    }
+
+    fn view_storage_notice(&self) -> Html<Self> {
+        if self.storage_service.is_persistent() || self.is_storage_notice_dismissed {
+            return html! {};
+        }
+
+        html! {
+            <div class="notification is-warning is-light has-text-centered is-size-7" role="note"
+                style="position: fixed; bottom: 2.5em; left: 50%; transform: translateX(-50%); z-index: 30;">
+                <button class="delete" type="button" aria-label="Dismiss"
+                    onclick=|_| Msg::DismissStorageNotice></button>
+                { "This browser does not allow local storage, so your session and settings are lost when the page is closed." }
+            </div>
+        }
+    }
+
+    fn view_idle_warning(&self) -> Html<Self> {
+        let deadline = match self.idle_deadline {
+            Some(deadline) if self.is_idle_warning_shown => deadline,
+            _ => return html! {},
+        };
+
+        let seconds = deadline
+            .signed_duration_since(Utc::now())
+            .num_seconds()
+            .max(0);
+
+        html! {
+            <div class="notification is-warning has-text-centered" role="alert"
+                style="position: fixed; top: 0.5em; left: 50%; transform: translateX(-50%); z-index: 35;">
+                <p>{ format!("You will be logged out in {}:{:02} due to inactivity.", seconds / 60, seconds % 60) }</p>
+                <div class="buttons is-centered" style="margin-top: 0.5em;">
+                    <button class="button is-small is-info" type="button"
+                        onclick=|_| Msg::SnoozeIdleLogout>
+                        { "Stay logged in" }
+                    </button>
+                    <button class="button is-small" type="button"
+                        onclick=|_| Msg::Logout>
+                        { "Log out now" }
+                    </button>
+                </div>
+            </div>
+        }
+    }
+
+    fn view_connection_status(&self) -> Html<Self> {
+        if self.is_reconnecting {
+            html! {
+                <div class="notification is-warning has-text-centered" role="status"
+                    style="position: fixed; top: 0.5em; left: 50%; transform: translateX(-50%); z-index: 30;">
+                    { format!("Reconnecting… (attempt {} of {})", self.reconnect_attempts, MAX_RECONNECT_ATTEMPTS) }
+                </div>
+            }
+        } else if self.is_connection_lost {
+            html! {
+                <div class="notification is-danger has-text-centered" role="alert"
+                    style="position: fixed; top: 0.5em; left: 50%; transform: translateX(-50%); z-index: 30;">
+                    { "Lost connection to the server" }
+                    <button class="button is-small" type="button" style="margin-left: 1em;"
+                        onclick=|_| Msg::KeepWaiting>
+                        { "Retry" }
+                    </button>
+                </div>
+            }
+        } else {
+            html! {}
+        }
+    }
+
+    fn view_test_connection(&self) -> Html<Self> {
+        if self.config.is_none() {
+            return html! {};
+        }
+
+        html! {
+            <div class="has-text-centered">
+                <button class=if self.is_connection_testing { "button is-small is-loading" } else { "button is-small" } type="button"
+                    disabled=self.is_connection_testing
+                    onclick=|_| Msg::TestConnection>
+                    { "Test API connection" }
+                </button>
+                {
+                    match &self.connection_test_result {
+                        Some(Ok(())) => html! {
+                            <p class="help is-success">{ "The API is reachable" }</p>
+                        },
+                        Some(Err(error)) => html! {
+                            <p class="help is-danger">{ error }</p>
+                        },
+                        None => html! {},
+                    }
+                }
+            </div>
+        }
+    }
+
+    fn view_maintenance(&self) -> Html<Self> {
+        html! {
+            <section class="hero is-fullheight">
+                <div class="hero-body">
+                    <div class="container">
+                        <div class="columns is-centered is-vcentered is-mobile">
+                            <div class="column" style="max-width: 400px;">
+                                <article class="message is-warning">
+                                    <div class="message-header">
+                                        <p>{ "Down for maintenance" }</p>
+                                    </div>
+                                    <div class="message-body">
+                                        <p>
+                                            {
+                                                self.maintenance_message.as_deref().unwrap_or(
+                                                    "The server is undergoing maintenance. This page will reconnect when it is back.",
+                                                )
+                                            }
+                                        </p>
+                                        <div class="buttons" style="margin-top: 1em;">
+                                            <button class=if self.is_fetch_slot_busy() { "button is-warning is-loading" } else { "button is-warning" }
+                                                type="button"
+                                                onclick=|_| Msg::FetchProfiles>
+                                                { "Retry now" }
+                                            </button>
+                                        </div>
+                                    </div>
+                                </article>
+                            </div>
+                        </div>
+                    </div>
+                </div>
+            </section>
+        }
+    }
+
+    fn view_error_fallback(&self) -> Html<Self> {
+        html! {
+            <section class="hero is-fullheight">
+                <div class="hero-body">
+                    <div class="container">
+                        <div class="columns is-centered is-vcentered is-mobile">
+                            <div class="column" style="max-width: 400px;">
+                                <article class="message is-danger">
+                                    <div class="message-header">
+                                        <p>{ "Something went wrong" }</p>
+                                    </div>
+                                    <div class="message-body">
+                                        <p>{ "Some data needed to show this page is missing." }</p>
+                                        <div class="buttons" style="margin-top: 1em;">
+                                            <button class="button is-danger" type="button"
+                                                onclick=|_| Msg::Reload>
+                                                { "Reload" }
+                                            </button>
+                                            <button class="button" type="button"
+                                                onclick=|_| Msg::Logout>
+                                                { "Logout" }
+                                            </button>
                                         </div>
-                                        <div class="field">
-                                            <div class="control has-icons-left">
-                                                <input class="input" type="password" placeholder="Password"
-                                                    oninput=|e| Msg::LoginRegisterFormDataChange(LoginRegisterFormDataField::Password, e.value) />
-                                                <span class="icon is-small is-left">
-                                                    <i class="fas fa-lock" />
-                                                </span>
-                                            </div>
+                                    </div>
+                                </article>
+                            </div>
+                        </div>
+                    </div>
+                </div>
+            </section>
+        }
+    }
+
+    fn view_history(&self) -> Html<Self> {
+        html! {
+            <section class="hero is-fullheight">
+                <div class="hero-body">
+                    <div class="container">
+                        <div class="columns is-centered is-mobile">
+                            <div class="column" style="max-width: 600px;">
+                                <nav class="panel">
+                                    <p class="panel-heading">
+                                        { "History" }
+                                    </p>
+                                    <div class="panel-block">
+                                        <div class="control has-icons-left">
+                                            <input class="input is-small" type="text" placeholder="File hash"
+                                                value=&self.history_hash_filter
+                                                oninput=|e| Msg::HistoryHashFilterChange(e.value) />
+                                            <span class="icon is-small is-left">
+                                                <i class="fas fa-search" />
+                                            </span>
                                         </div>
-                                        <div class="level is-mobile">
-                                            <div class="level-left">
-                                                <div class="level-item">
-                                                    <div class="field">
-                                                        <button class=if self.is_register_loading { "button is-loading" } else { "button" } type="button"
-                                                            disabled=self.is_register_disabled
-                                                            onclick=|_| Msg::Register>
-                                                            { "Register" }
-                                                        </button>
-                                                    </div>
-                                                </div>
+                                    </div>
+                                    <div class="panel-block">
+                                        <div class="field is-grouped">
+                                            <div class="control">
+                                                <input class="input is-small" type="date" title="From"
+                                                    onchange=|e| {
+                                                        if let ChangeData::Value(value) = e {
+                                                            Msg::HistoryDateFromChange(value)
+                                                        } else {
+                                                            Msg::NoOp
+                                                        }
+                                                    } />
                                             </div>
-                                            <div class="level-right">
-                                                <div class="level-item">
-                                                    <div class="field">
-                                                        <button class=if self.is_login_loading { "button is-loading" } else { "button" } type="button"
-                                                            disabled=self.is_login_disabled
-                                                            onclick=|_| Msg::Login>
-                                                            { "Login" }
-                                                        </button>
-                                                    </div>
-                                                </div>
+                                            <div class="control">
+                                                <input class="input is-small" type="date" title="To"
+                                                    onchange=|e| {
+                                                        if let ChangeData::Value(value) = e {
+                                                            Msg::HistoryDateToChange(value)
+                                                        } else {
+                                                            Msg::NoOp
+                                                        }
+                                                    } />
                                             </div>
                                         </div>
                                     </div>
+                                    { self.view_history_reports() }
+                                </nav>
+                                <div class="has-text-centered" style="margin-top: 2em; margin-bottom: 2em;">
+                                    <button class="button" type="button" onclick=|_| Msg::CloseHistory>
+                                        { "Back" }
+                                    </button>
                                 </div>
                             </div>
                         </div>
                     </div>
-                </section>
-            },
-            Scene::FetchConfigError => html! {
-                <section class="hero is-fullheight">
-                    <div class="hero-body">
-                        <div class="container">
-                            <div class="columns is-centered is-vcentered is-mobile">
-                                <div class="column">
-                                    <div class="has-text-centered">
-                                        <span class="icon has-text-danger">
-                                            <i class="fas fa-info-circle" />
-                                        </span>
-                                        { "Could not fetch configuration, please reload to try again." }
-                                    </div>
-                                </div>
-                            </div>
-                        </div>
+                </div>
+            </section>
+        }
+    }
+
+    fn view_history_reports(&self) -> Html<Self> {
+        if let Some(error) = &self.history_error {
+            return html! {
+                <p class="panel-block has-text-danger">{ error }</p>
+            };
+        }
+
+        let reports = match &self.history_reports {
+            Some(reports) => filter_reports(
+                reports,
+                &self.history_hash_filter,
+                self.history_date_from,
+                self.history_date_to,
+            ),
+            None => {
+                return html! {
+                    <div class="panel-block">
+                        <progress class="progress is-small is-dark" max="100" />
                     </div>
-                </section>
+                }
+            }
+        };
+
+        if reports.is_empty() {
+            return html! {
+                <p class="panel-block has-text-grey">{ "No reports match" }</p>
+            };
+        }
+
+        html! {
+            <table class="table is-striped is-narrow is-hoverable is-fullwidth">
+                <thead>
+                    <tr>
+                        <th>{ "Date" }</th>
+                        <th>{ "Hash" }</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    {
+                        for reports.iter().map(|report| html! {
+                            <tr>
+                                <td>{ report.created_when.format("%Y-%m-%d %H:%M") }</td>
+                                <td style="word-break: break-all;">{ &report.file_multihash }</td>
+                            </tr>
+                        })
+                    }
+                </tbody>
+            </table>
+        }
+    }
+
+    fn view_upload_limit(&self) -> Html<Self> {
+        match self.max_upload_bytes() {
+            Some(limit) => html! {
+                <p class="help has-text-centered">{ format!("Max {}", format_file_size(limit)) }</p>
             },
-            Scene::LoggedIn => html! {
-                <section class="hero is-fullheight">
-                    <div class="hero-body">
-                        <div class="container">
-                            <div class="columns is-centered is-vcentered is-mobile">
-                                <div class="column" style="max-width: 400px;">
-                                    <nav class="panel">
-                                        <p class="panel-heading">
-                                            { "Profiles" }
-                                        </p>
-                                        <table class="table is-bordered is-striped is-narrow is-hoverable is-fullwidth">
-                                        <thead>
-                                            <tr>
-                                                <th>{ "Engine" }</th>
-                                                <th>{ "Status" }</th>
-                                            </tr>
-                                        </thead>
-                                        <tbody>
+            None => html! {},
+        }
+    }
+
+    fn view_create_report_rejection(&self) -> Html<Self> {
+        let (title, detail, guidance) = match &self.create_report_rejection {
+            Some(CreateReportRejection::TooLarge) => (
+                "File too large",
+                "The server refused this file because it exceeds the upload size limit.".to_owned(),
+                "Try compressing the file, or scan a smaller sample.",
+            ),
+            Some(CreateReportRejection::QuotaExceeded(usage)) => (
+                "Scan quota exceeded",
+                match (usage.used, usage.limit) {
+                    (Some(used), Some(limit)) => {
+                        format!("You have used {} of your {} scans.", used, limit)
+                    }
+                    (None, Some(limit)) => format!("Your plan allows {} scans.", limit),
+                    _ => "You have reached the number of scans your plan allows.".to_owned(),
+                },
+                "Wait for your quota to reset, or upgrade your plan.",
+            ),
+            None => return html! {},
+        };
+
+        html! {
+            <article class="message is-warning" style="margin-top: 1em;">
+                <div class="message-header">
+                    <p>{ title }</p>
+                </div>
+                <div class="message-body">
+                    <p>{ detail }</p>
+                    <p>{ guidance }</p>
+                </div>
+            </article>
+        }
+    }
+
+    fn view_hash_lookup(&self) -> Html<Self> {
+        html! {
+            <div style="margin-top: 1em;">
+                <div class="field has-addons">
+                    <div class="control is-expanded has-icons-left">
+                        <input class="input" type="text" placeholder="Look up a file hash"
+                            value=&self.lookup_hash
+                            oninput=|e| Msg::LookupHashChange(e.value) />
+                        <span class="icon is-small is-left">
+                            <i class="fas fa-fingerprint" />
+                        </span>
+                    </div>
+                    <div class="control">
+                        <button class=if self.is_lookup_loading { "button is-loading" } else { "button" } type="button"
+                            disabled=self.is_lookup_loading
+                            onclick=|_| Msg::LookupHash>
+                            { "Look up" }
+                        </button>
+                    </div>
+                </div>
+                {
+                    if let Some(error) = &self.lookup_error {
+                        html! { <p class="help is-danger">{ error }</p> }
+                    } else {
+                        html! {}
+                    }
+                }
+                {
+                    match &self.lookup_reports {
+                        Some(reports) if reports.is_empty() => html! {
+                            <p class="help">{ "No prior scans" }</p>
+                        },
+                        Some(reports) => html! {
+                            <div class="panel">
+                                {
+                                    for reports.iter().map(|report| {
+                                        let report_id = report.id;
+                                        html! {
+                                            <a class="panel-block" href="#" onclick=|e| { e.prevent_default(); Msg::ShowSessionReport(report_id) }>
+                                                <span class="panel-icon">
+                                                    <i class="fas fa-file"></i>
+                                                </span>
+                                                { format!("Report #{}", report.id) }
+                                                <span class="has-text-grey" style="margin-left: auto;">
+                                                    { report.created_when.format("%Y-%m-%d %H:%M").to_string() }
+                                                </span>
+                                            </a>
+                                        }
+                                    })
+                                }
+                            </div>
+                        },
+                        None => html! {},
+                    }
+                }
+            </div>
+        }
+    }
+
+    fn view_scan_phase(&self) -> Html<Self> {
+        let current = match self.scan_phase() {
+            Some(current) => current,
+            None => return html! {},
+        };
+
+        let phases = [
+            ScanPhase::Reading,
+            ScanPhase::Uploading,
+            ScanPhase::Scanning,
+            ScanPhase::Done,
+        ];
+
+        html! {
+            <div class="tags is-centered" style="margin-bottom: 1em;">
+                {
+                    for phases.iter().map(|phase| {
+                        let class = if *phase == current {
+                            "tag is-info"
+                        } else if *phase < current {
+                            "tag is-success is-light"
+                        } else {
+                            "tag is-light"
+                        };
+                        html! { <span class=class>{ phase.label() }</span> }
+                    })
+                }
+            </div>
+        }
+    }
+
+    /// One tab per report of this session once there is more than one to switch between.
+    fn view_report_tabs(&self) -> Html<Self> {
+        if self.session_reports.len() < 2 {
+            return html! {};
+        }
+
+        html! {
+            <div class="tabs is-small is-centered">
+                <ul>
+                    {
+                        for self.session_reports.iter().map(|session_report| {
+                            let report_id = session_report.report_id;
+                            let label = if session_report.file_name.is_empty() {
+                                format!("Report #{}", report_id)
+                            } else {
+                                session_report.file_name.to_owned()
+                            };
+
+                            html! {
+                                <li class=if self.current_report_id == Some(report_id) { "is-active" } else { "" }>
+                                    <a href="#" title=format!("Report #{}", report_id)
+                                        onclick=|e| { e.prevent_default(); Msg::SelectReportTab(report_id) }>
                                         {
-                                            for self.fetched_profiles.iter().next().unwrap().profiles.iter().map(|profile| {
+                                            if session_report.summary.is_none() {
                                                 html! {
-                                                    <tr>
-                                                        <td>
-                                                            <input
-                                                                type="checkbox"
-                                                                checked=true
-                                                                value=&profile.machine_name.to_string()
-                                                                onchange=|e| {
-                                                                    if let ChangeData::Value(value) = e {
-                                                                        Msg::ToggleProfile(value)
-                                                                    } else {
-                                                                        Msg::NoOp
-                                                                    }
-                                                                }
-                                                            />
-                                                            { &profile.human_name }
-                                                        </td>
-                                                        <td>
-                                                            {
-                                                                if let Some(tasks) = &self.current_pending_tasks {
-                                                                    let task = tasks.iter().find(|x| x.profile_id == profile.id);
-                                                                    if let Some(task) = task {
-                                                                        match task.status.as_str() {
-                                                                            "new" => "Waiting for worker..",
-                                                                            "pending" => "Processing..",
-                                                                            "clean" => "Clean",
-                                                                            "detected" => task.message.iter().next().unwrap(),
-                                                                            "timeout" => "Timeout",
-                                                                            "error" => "Error",
-                                                                            _ => ""
-                                                                        }
-                                                                    } else {
-                                                                        ""
-                                                                    }
-                                                                } else {
-                                                                    "Idle"
-                                                                }
-                                                            }
-                                                        </td>
-                                                    </tr>
+                                                    <span class="icon is-small">
+                                                        <i class="fas fa-spinner fa-pulse"></i>
+                                                    </span>
                                                 }
-                                            })
+                                            } else {
+                                                html! {}
+                                            }
                                         }
-                                        </tbody>
-                                        </table>
-                                    </nav>
+                                        <span>{ label }</span>
+                                    </a>
+                                </li>
+                            }
+                        })
+                    }
+                </ul>
+            </div>
+        }
+    }
 
-                                    <div class="file is-boxed is-centered">
-                                        <label class="file-label">
-                                            {
-                                                if self.is_file_uploading {
-                                                    html! { <input class="file-input" type="file" disabled=true onchange=|e| Msg::LoadFile(e) /> }
-                                                } else {
-                                                    html! { <input class="file-input" type="file" onchange=|e| Msg::LoadFile(e) /> }
-                                                }
-                                            }
-                                            <span class="file-cta">
-                                                <span class="file-icon">
-                                                    <i class="fas fa-upload"></i>
+    fn view_previous_report_toggle(&self) -> Html<Self> {
+        let previous_report = match &self.previous_report {
+            Some(previous_report) => previous_report,
+            None => return html! {},
+        };
+
+        html! {
+            <div class="has-text-centered" style="margin-bottom: 1em;">
+                {
+                    if self.is_viewing_previous_report {
+                        html! {
+                            <p class="is-size-7 has-text-grey">
+                                { format!("Showing the previous result (report #{})", previous_report.report_id) }
+                            </p>
+                        }
+                    } else {
+                        html! {}
+                    }
+                }
+                <button class="button is-small is-light" onclick=|_| Msg::TogglePreviousReport>
+                    {
+                        if self.is_viewing_previous_report {
+                            "View current result"
+                        } else {
+                            "View previous result"
+                        }
+                    }
+                </button>
+            </div>
+        }
+    }
+
+    /// The report id support staff ask for, with a button to copy it.
+    fn view_report_id(&self) -> Html<Self> {
+        let report_id = match self.current_report_id {
+            Some(report_id) => report_id,
+            None => return html! {},
+        };
+
+        html! {
+            <p class="is-size-7 has-text-centered has-text-grey" style="margin-bottom: 0.5em;">
+                { "Report " }
+                <code id="report-id">{ report_id }</code>
+                {
+                    match self.current_report_created_when {
+                        Some(created_when) => html! {
+                            <span>{ format!(" · started {}", format_elapsed(created_when, Utc::now())) }</span>
+                        },
+                        None => html! {},
+                    }
+                }
+                <button class="button is-small is-text" type="button" title="Copy report ID"
+                    onclick=|_| Msg::CopyReportId(report_id)>
+                    {
+                        if self.copied_tt.is_some() {
+                            html! { <span>{ "Copied" }</span> }
+                        } else {
+                            html! {
+                                <span class="icon is-small">
+                                    <i class="fas fa-copy"></i>
+                                </span>
+                            }
+                        }
+                    }
+                </button>
+            </p>
+        }
+    }
+
+    fn view_clear_results(&self) -> Html<Self> {
+        if self.current_pending_tasks.is_none() && !self.is_polling() {
+            return html! {};
+        }
+
+        html! {
+            <div class="has-text-centered" style="margin-bottom: 1em;">
+                <button class="button is-small is-light" type="button"
+                    onclick=|_| Msg::ClearResults>
+                    { "Clear results" }
+                </button>
+            </div>
+        }
+    }
+
+    fn view_check_status(&self) -> Html<Self> {
+        let report_id = match self.manual_poll_report_id {
+            Some(report_id) => report_id,
+            None => return html! {},
+        };
+
+        html! {
+            <div class="has-text-centered" style="margin-bottom: 1em;">
+                <button class=if self.is_polling_fetch_in_flight { "button is-info is-loading" } else { "button is-info" }
+                    type="button"
+                    onclick=|_| Msg::FetchTasks(report_id)>
+                    { "Check status" }
+                </button>
+            </div>
+        }
+    }
+
+    fn view_retry_failed(&self) -> Html<Self> {
+        if !self.is_scan_complete() || self.is_viewing_previous_report {
+            return html! {};
+        }
+
+        let failed_count = self.failed_machine_names().len();
+        if failed_count == 0 {
+            return html! {};
+        }
+
+        html! {
+            <div class="has-text-centered" style="margin-bottom: 1em;">
+                <button class=if self.is_retry_failed_loading { "button is-warning is-loading" } else { "button is-warning" }
+                    type="button"
+                    onclick=|_| Msg::RetryFailed>
+                    { format!("Retry all failed ({})", failed_count) }
+                </button>
+            </div>
+        }
+    }
+
+    fn view_scan_score(&self) -> Html<Self> {
+        if !self.is_scan_complete() {
+            return html! {};
+        }
+
+        let score = self
+            .current_pending_tasks
+            .as_ref()
+            .and_then(|tasks| scan_score(tasks));
+        let (detected, total) = match score {
+            Some(score) => score,
+            None => return html! {},
+        };
+
+        html! {
+            <div class=scan_score_class(detected, total)>
+                <p class="is-size-5 has-text-weight-bold">
+                    { format!("{}/{} engines flagged this file", detected, total) }
+                </p>
+            </div>
+        }
+    }
+
+    fn view_jump_to_detection(&self) -> Html<Self> {
+        if self.displayed_tasks().is_none() {
+            return html! {};
+        }
+
+        let has_detections = !self.detected_profile_ids().is_empty();
+
+        html! {
+            <div class="has-text-centered" style="margin-bottom: 1em;">
+                <button class="button is-small" type="button" title="Shortcut: j"
+                    disabled=!has_detections
+                    onclick=|_| Msg::JumpToDetection>
+                    <span class="icon is-small">
+                        <i class="fas fa-crosshairs"></i>
+                    </span>
+                    <span>{ "Jump to detections" }</span>
+                </button>
+            </div>
+        }
+    }
+
+    fn view_polling_indicator(&self) -> Html<Self> {
+        if self.it.is_none() {
+            return html! {};
+        }
+
+        let (class, title) = if self.is_polling_fetch_in_flight {
+            (
+                "icon is-small has-text-info polling-indicator is-fetching",
+                "Checking for results…",
+            )
+        } else {
+            (
+                "icon is-small has-text-grey-light polling-indicator",
+                "Waiting for the next check",
+            )
+        };
+
+        html! {
+            <span class=class title=title style="margin-left: 0.5em;">
+                <i class="fas fa-circle fa-xs"></i>
+            </span>
+        }
+    }
+
+    fn view_copy_as_curl(&self) -> Html<Self> {
+        let is_debug = self.config.as_ref().is_some_and(|x| x.debug);
+
+        if is_debug && self.last_request.is_some() {
+            html! {
+                <button class="button is-small is-text" type="button"
+                    onclick=|_| Msg::CopyLastRequestAsCurl>
+                    { "Copy as curl" }
+                </button>
+            }
+        } else {
+            html! {}
+        }
+    }
+
+    fn view_config_template_download(&self) -> Html<Self> {
+        if self.config.as_ref().is_some_and(|x| x.debug) {
+            html! {
+                <button class="button is-small is-text" type="button"
+                    onclick=|_| Msg::DownloadConfigTemplate>
+                    { "Config template" }
+                </button>
+            }
+        } else {
+            html! {}
+        }
+    }
+
+    fn view_modal(&self) -> Html<Self> {
+        let modal = match &self.modal {
+            Some(modal) => modal,
+            None => return html! {},
+        };
+
+        html! {
+            <div class="modal is-active">
+                <div class="modal-background" />
+                <div class="modal-card">
+                    <header class="modal-card-head">
+                        <p class="modal-card-title">{ &modal.title }</p>
+                    </header>
+                    <section class="modal-card-body">
+                        { for modal.body.lines().map(|line| html! { <p>{ line }</p> }) }
+                    </section>
+                    <footer class="modal-card-foot">
+                        {
+                            for modal.actions.iter().map(|action| {
+                                let msg = action.msg;
+                                html! {
+                                    <button class=action.class type="button" onclick=|_| msg()>
+                                        { action.label }
+                                    </button>
+                                }
+                            })
+                        }
+                    </footer>
+                </div>
+            </div>
+        }
+    }
+
+    fn view_password_reset(&self) -> Html<Self> {
+        html! {
+            <section class="hero is-fullheight">
+                <div class="hero-body">
+                    <div class="container">
+                        <div class="columns is-centered is-vcentered is-mobile">
+                            <div class="column" style="max-width: 300px;">
+                                {
+                                    if let Some(error) = &self.password_reset_error {
+                                        html! {
+                                            <p class="has-text-centered" style="margin-top: 1em; margin-bottom: 1em;">
+                                                <span class="icon has-text-danger">
+                                                    <i class="fas fa-info-circle"></i>
                                                 </span>
-                                                <span class="file-label">
-                                                    { "Drag to scan" }
+                                                { error }
+                                            </p>
+                                        }
+                                    } else if let Some(message) = &self.password_reset_message {
+                                        html! {
+                                            <p class="has-text-centered" style="margin-top: 1em; margin-bottom: 1em;">
+                                                <span class="icon has-text-success">
+                                                    <i class="fas fa-info-circle"></i>
+                                                </span>
+                                                { message }
+                                            </p>
+                                        }
+                                    } else {
+                                        html! {
+                                            <p class="has-text-centered" style="margin-top: 1em; margin-bottom: 1em;">
+                                                <span class="icon has-text-info">
+                                                    <i class="fas fa-info-circle"></i>
                                                 </span>
+                                                { "Enter your username or email" }
+                                            </p>
+                                        }
+                                    }
+                                }
+                                <div class="box is-centered">
+                                    <div class="field">
+                                        <div class="control has-icons-left">
+                                            <input class="input" type="text" placeholder="Username or email"
+                                                oninput=|e| Msg::PasswordResetFormDataChange(e.value) />
+                                            <span class="icon is-small is-left">
+                                                <i class="fas fa-user" />
                                             </span>
-                                        </label>
+                                        </div>
                                     </div>
-
-                                    <div class="has-text-centered" style="margin-top: 2em; margin-bottom: 2em;">
-                                        <button class=format!("button {} {}",
-                                            if self.is_logout_loading { "is-loading" } else {""},
-                                            if self.logout_error.is_some() {"is-danger"} else {""}),
-                                            type="button"
-                                            disabled=self.is_logout_disabled
-                                            onclick=|_| Msg::Logout>
-                                            { "Logout" }
-                                        </button>
+                                    <div class="level is-mobile">
+                                        <div class="level-left">
+                                            <div class="level-item">
+                                                <div class="field">
+                                                    <button class="button" type="button"
+                                                        onclick=|_| Msg::SetAuthView(AuthView::LoginRegister)>
+                                                        { "Back" }
+                                                    </button>
+                                                </div>
+                                            </div>
+                                        </div>
+                                        <div class="level-right">
+                                            <div class="level-item">
+                                                <div class="field">
+                                                    <button class=if self.is_password_reset_loading { "button is-loading" } else { "button" } type="button"
+                                                        disabled=self.is_password_reset_loading
+                                                        onclick=|_| Msg::RequestPasswordReset>
+                                                        { "Send" }
+                                                    </button>
+                                                </div>
+                                            </div>
+                                        </div>
                                     </div>
                                 </div>
                             </div>
                         </div>
                     </div>
-                </section>
-            },
+                </div>
+            </section>
         }
     }
 }
@@ -799,3 +6668,9 @@ impl Renderable<Model> for Model {
 fn main() {
     yew::start_app::<Model>();
 }
+
+#[cfg(feature = "pdf_export")]
+mod pdf;
+
+#[cfg(test)]
+mod tests;
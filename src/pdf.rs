@@ -0,0 +1,138 @@
+//! The printable scan report behind "Download PDF", built with `pdf-writer` so the client does
+//! not have to produce the file format itself.
+
+use chrono::{DateTime, Utc};
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref, Str};
+
+use crate::{summarize_tasks, task_status_label, Profile, Task};
+
+const LINES_PER_PAGE: usize = 50;
+
+/// Text lines of the printable report: metadata first, then one line per engine verdict.
+pub fn report_lines(
+    file_name: &str,
+    hash: Option<&str>,
+    created_when: Option<DateTime<Utc>>,
+    tasks: &[Task],
+    profiles: &[Profile],
+) -> Vec<String> {
+    let mut lines = vec![
+        "Violetear scan report".to_owned(),
+        String::new(),
+        format!("File: {}", file_name),
+        format!("Hash: {}", hash.unwrap_or("unknown")),
+        format!(
+            "Date: {}",
+            created_when.map_or_else(|| "unknown".to_owned(), |x| x.to_rfc2822())
+        ),
+        format!("Summary: {}", summarize_tasks(tasks)),
+        String::new(),
+    ];
+
+    lines.extend(tasks.iter().map(|task| {
+        let engine = profiles
+            .iter()
+            .find(|x| x.id == task.profile_id)
+            .map_or("unknown engine", |x| x.human_name.as_str());
+        format!("{}: {}", engine, task_status_label(&task.status))
+    }));
+
+    lines
+}
+
+/// Lays the lines out in 11 pt Helvetica, 50 to an A4 page.
+pub fn render(lines: &[String]) -> Vec<u8> {
+    let catalog_id = Ref::new(1);
+    let page_tree_id = Ref::new(2);
+    let font_id = Ref::new(3);
+    let font_name = Name(b"F1");
+
+    let pages: Vec<&[String]> = if lines.is_empty() {
+        vec![&[]]
+    } else {
+        lines.chunks(LINES_PER_PAGE).collect()
+    };
+    // Every page is followed by its content stream.
+    let page_ids: Vec<Ref> = (0..pages.len())
+        .map(|x| Ref::new(4 + 2 * x as i32))
+        .collect();
+
+    let mut pdf = Pdf::new();
+    pdf.catalog(catalog_id).pages(page_tree_id);
+    pdf.pages(page_tree_id)
+        .kids(page_ids.iter().copied())
+        .count(pages.len() as i32);
+    pdf.type1_font(font_id)
+        .base_font(Name(b"Helvetica"))
+        .encoding_predefined(Name(b"WinAnsiEncoding"));
+
+    for (page_id, page_lines) in page_ids.into_iter().zip(pages) {
+        let content_id = Ref::new(page_id.get() + 1);
+        let mut page = pdf.page(page_id);
+        page.parent(page_tree_id)
+            .media_box(Rect::new(0.0, 0.0, 595.0, 842.0))
+            .contents(content_id);
+        page.resources().fonts().pair(font_name, font_id);
+        page.finish();
+
+        let mut content = Content::new();
+        content
+            .begin_text()
+            .set_font(font_name, 11.0)
+            .set_leading(14.0)
+            .next_line(50.0, 790.0);
+        for line in page_lines {
+            content
+                .show(Str(&encode_win_ansi(line)))
+                .next_line_using_leading();
+        }
+        content.end_text();
+        pdf.stream(content_id, &content.finish());
+    }
+
+    pdf.finish()
+}
+
+/// The standard fonts only cover WinAnsiEncoding, so characters outside it become `?`.
+pub fn encode_win_ansi(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|x| win_ansi_code(x).unwrap_or(b'?'))
+        .collect()
+}
+
+/// The WinAnsiEncoding code of a printable character: Latin-1, plus the typographic characters
+/// Windows-1252 places in 0x80 to 0x9F.
+fn win_ansi_code(character: char) -> Option<u8> {
+    let code = match character {
+        ' '..='~' | '\u{a0}'..='\u{ff}' => character as u32 as u8,
+        '€' => 0x80,
+        '‚' => 0x82,
+        'ƒ' => 0x83,
+        '„' => 0x84,
+        '…' => 0x85,
+        '†' => 0x86,
+        '‡' => 0x87,
+        'ˆ' => 0x88,
+        '‰' => 0x89,
+        'Š' => 0x8a,
+        '‹' => 0x8b,
+        'Œ' => 0x8c,
+        'Ž' => 0x8e,
+        '‘' => 0x91,
+        '’' => 0x92,
+        '“' => 0x93,
+        '”' => 0x94,
+        '•' => 0x95,
+        '–' => 0x96,
+        '—' => 0x97,
+        '˜' => 0x98,
+        '™' => 0x99,
+        'š' => 0x9a,
+        '›' => 0x9b,
+        'œ' => 0x9c,
+        'ž' => 0x9e,
+        'Ÿ' => 0x9f,
+        _ => return None,
+    };
+    Some(code)
+}
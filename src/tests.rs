@@ -0,0 +1,2454 @@
+use super::*;
+
+use std::cell::Cell;
+use std::sync::Once;
+use yew::virtual_dom::VTag;
+
+/// Natively there is no JavaScript behind `js!`; every snippet evaluates to `undefined`.
+#[no_mangle]
+extern "C" fn emscripten_asm_const_int() -> i32 {
+    0
+}
+
+fn setup() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        stdweb::initialize();
+        // stdweb reports panics to the browser console, keep the default test output.
+        let _ = std::panic::take_hook();
+    });
+}
+
+type Responder = Box<dyn FnOnce(u16, Vec<u8>)>;
+
+struct MockRequest {
+    method: String,
+    uri: String,
+    headers: http::HeaderMap,
+    body: Vec<u8>,
+    active: Rc<Cell<bool>>,
+    responder: Option<Responder>,
+}
+
+impl MockRequest {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|x| x.to_str().ok())
+    }
+
+    fn body_text(&self) -> &str {
+        std::str::from_utf8(&self.body).unwrap()
+    }
+}
+
+/// Records every request and lets the test answer them one at a time.
+#[derive(Default)]
+struct MockApi {
+    requests: Vec<MockRequest>,
+}
+
+impl MockApi {
+    fn record(
+        &mut self,
+        parts: http::request::Parts,
+        body: Vec<u8>,
+        responder: Responder,
+    ) -> MockTask {
+        let active = Rc::new(Cell::new(true));
+        self.requests.push(MockRequest {
+            method: parts.method.to_string(),
+            uri: parts.uri.to_string(),
+            headers: parts.headers,
+            body,
+            active: active.clone(),
+            responder: Some(responder),
+        });
+        MockTask(active)
+    }
+}
+
+struct MockTask(Rc<Cell<bool>>);
+
+impl yew::services::Task for MockTask {
+    fn is_active(&self) -> bool {
+        self.0.get()
+    }
+
+    fn cancel(&mut self) {
+        self.0.set(false);
+    }
+}
+
+impl Drop for MockTask {
+    fn drop(&mut self) {
+        self.0.set(false);
+    }
+}
+
+impl Api for MockApi {
+    type Task = MockTask;
+
+    fn fetch<IN, OUT>(
+        &mut self,
+        request: Request<IN>,
+        callback: Callback<Response<OUT>>,
+    ) -> MockTask
+    where
+        IN: Into<Text>,
+        OUT: From<Text> + 'static,
+    {
+        let (parts, body) = request.into_parts();
+        let body: Text = body.into();
+        self.record(
+            parts,
+            body.unwrap_or_default().into_bytes(),
+            Box::new(move |status, body| {
+                let body = String::from_utf8(body).map_err(Error::from);
+                callback.emit(
+                    Response::builder()
+                        .status(status)
+                        .body(OUT::from(body))
+                        .unwrap(),
+                );
+            }),
+        )
+    }
+
+    fn fetch_binary<IN, OUT>(
+        &mut self,
+        request: Request<IN>,
+        callback: Callback<Response<OUT>>,
+    ) -> MockTask
+    where
+        IN: Into<Binary>,
+        OUT: From<Binary> + 'static,
+    {
+        let (parts, body) = request.into_parts();
+        let body: Binary = body.into();
+        self.record(
+            parts,
+            body.unwrap_or_default(),
+            Box::new(move |status, body| {
+                callback.emit(
+                    Response::builder()
+                        .status(status)
+                        .body(OUT::from(Ok(body)))
+                        .unwrap(),
+                );
+            }),
+        )
+    }
+}
+
+const CONFIG: &str = r#"{"api_url": "http://api.test"}"#;
+
+const PROFILES: &str = r#"{"profiles": [
+    {"id": 1, "machine_name": "clamav", "human_name": "ClamAV", "module": "clamav", "config": null},
+    {"id": 2, "machine_name": "yara", "human_name": "YARA", "module": "yara", "config": null}
+]}"#;
+
+/// A `Model<MockApi>` whose messages to itself are processed as soon as the test's own are.
+struct Harness {
+    model: Model<MockApi>,
+    queue: Rc<RefCell<VecDeque<Msg>>>,
+}
+
+impl Harness {
+    fn new() -> Self {
+        Self::with_storage(LocalStore::Memory(HashMap::new()))
+    }
+
+    fn with_storage(storage: LocalStore) -> Self {
+        setup();
+        let queue = Rc::new(RefCell::new(VecDeque::new()));
+        let model = Model::new(MessageLink::Queue(queue.clone()), storage, false, false);
+        Harness { model, queue }
+    }
+
+    /// Starts from what an earlier page load saved, e.g. `&[(KEY, state)]`.
+    fn restored(entries: &[(&str, &str)]) -> Self {
+        let storage = entries
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        Self::with_storage(LocalStore::Memory(storage))
+    }
+
+    fn with_config(config: &str) -> Self {
+        let mut harness = Self::new();
+        harness.configure(config);
+        harness
+    }
+
+    /// Configured and logged in with `PROFILES`.
+    fn logged_in() -> Self {
+        Self::logged_in_with(CONFIG, PROFILES)
+    }
+
+    fn logged_in_with(config: &str, profiles: &str) -> Self {
+        let mut harness = Self::with_config(config);
+        harness.model.loginregister_form.username = "alice".into();
+        harness.send(Msg::Login);
+        harness.respond(
+            "/v1/auth/login",
+            200,
+            r#"{"token": "t0k", "refresh_token": "r3f"}"#,
+        );
+        harness.respond("/v1/profiles", 200, profiles);
+        assert_eq!(harness.model.scene, Scene::LoggedIn);
+        harness
+    }
+
+    fn configure(&mut self, config: &str) {
+        let config: Config = serde_json::from_str(config).unwrap();
+        self.send(Msg::FetchConfigDone(Ok(Box::new(config))));
+    }
+
+    fn send(&mut self, msg: Msg) -> ShouldRender {
+        let should_render = self.model.update(msg);
+        self.drain();
+        should_render
+    }
+
+    fn drain(&mut self) {
+        for _ in 0..1000 {
+            let msg = self.queue.borrow_mut().pop_front();
+            match msg {
+                Some(msg) => {
+                    self.model.update(msg);
+                }
+                None => return,
+            }
+        }
+        panic!("the model keeps sending itself messages");
+    }
+
+    /// Answers the oldest unanswered request still in flight whose URI path ends with `path`.
+    fn respond(&mut self, path: &str, status: u16, body: &str) {
+        let responder = self
+            .model
+            .fetch_service
+            .requests
+            .iter_mut()
+            .find(|x| {
+                x.active.get()
+                    && x.responder.is_some()
+                    && x.uri.split('?').next().unwrap().ends_with(path)
+            })
+            .map(|x| {
+                x.active.set(false);
+                x.responder.take().unwrap()
+            })
+            .unwrap_or_else(|| panic!("no request in flight for {}", path));
+        responder(status, body.as_bytes().to_vec());
+        self.drain();
+    }
+
+    fn is_in_flight(&self, path: &str) -> bool {
+        self.model
+            .fetch_service
+            .requests
+            .iter()
+            .any(|x| x.active.get() && x.responder.is_some() && x.uri.contains(path))
+    }
+
+    fn requests(&self, path: &str) -> Vec<&MockRequest> {
+        self.model
+            .fetch_service
+            .requests
+            .iter()
+            .filter(|x| x.uri.contains(path))
+            .collect()
+    }
+
+    fn last_request(&self, path: &str) -> &MockRequest {
+        self.requests(path)
+            .pop()
+            .unwrap_or_else(|| panic!("no request for {}", path))
+    }
+
+    fn stored(&self, key: &str) -> Option<String> {
+        match &self.model.storage_service {
+            LocalStore::Memory(map) => map.get(key).cloned(),
+            LocalStore::Full => None,
+            LocalStore::Browser(_) => unreachable!(),
+        }
+    }
+
+    /// The text content of the whole page, with one space between nodes.
+    fn rendered_text(&self) -> String {
+        fn collect(node: &Html<Model<MockApi>>, text: &mut String) {
+            match node {
+                VNode::VTag(tag) => tag.childs.iter().for_each(|x| collect(x, text)),
+                VNode::VList(list) => list.childs.iter().for_each(|x| collect(x, text)),
+                VNode::VText(node) => {
+                    text.push_str(&node.text);
+                    text.push(' ');
+                }
+                VNode::VComp(_) | VNode::VRef(_) => {}
+            }
+        }
+
+        let mut text = String::new();
+        collect(&self.model.view(), &mut text);
+        text
+    }
+
+    /// Clicks the button labelled `label` in the open modal.
+    fn click_modal_action(&mut self, label: &str) {
+        let msg = self
+            .model
+            .modal
+            .as_ref()
+            .and_then(|x| x.actions.iter().find(|x| x.label == label))
+            .map(|x| (x.msg)())
+            .unwrap_or_else(|| panic!("no {} button", label));
+        self.send(msg);
+    }
+
+    fn upload(&mut self, name: &str, content: &[u8]) {
+        self.model.is_file_uploading = true;
+        self.send(Msg::CreateReport(FileData {
+            name: name.into(),
+            content: content.to_vec(),
+        }));
+    }
+}
+
+fn tasks_json(statuses: &[&str]) -> String {
+    let tasks: Vec<String> = statuses
+        .iter()
+        .enumerate()
+        .map(|(i, status)| {
+            format!(
+                r#"{{"id": {}, "report_id": 7, "profile_id": {}, "created_when": "2020-01-01T00:00:00Z",
+                    "completed_when": null, "status": "{}", "message": null}}"#,
+                10 + i,
+                1 + i,
+                status
+            )
+        })
+        .collect();
+    format!(r#"{{"tasks": [{}]}}"#, tasks.join(","))
+}
+
+#[test]
+fn login_fetches_profiles() {
+    let mut harness = Harness::with_config(CONFIG);
+    assert_eq!(harness.model.scene, Scene::LoginRegister);
+
+    harness.model.loginregister_form.username = "alice".into();
+    harness.send(Msg::Login);
+    let login = harness.last_request("/v1/auth/login");
+    assert_eq!(login.method, "POST");
+    assert!(login.body_text().contains(r#""username":"alice""#));
+
+    harness.respond("/v1/auth/login", 200, r#"{"token": "t0k"}"#);
+    assert_eq!(harness.model.state.token.as_deref(), Some("t0k"));
+    let profiles = harness.last_request("/v1/profiles");
+    assert_eq!(profiles.method, "GET");
+    assert_eq!(profiles.header("Authorization"), Some("t0k"));
+
+    harness.respond("/v1/profiles", 200, PROFILES);
+    assert_eq!(harness.model.scene, Scene::LoggedIn);
+    assert_eq!(
+        harness
+            .model
+            .fetched_profiles
+            .as_ref()
+            .unwrap()
+            .profiles
+            .len(),
+        2
+    );
+    assert_eq!(
+        harness.model.enabled_profiles,
+        [1, 2].iter().cloned().collect()
+    );
+}
+
+#[test]
+fn upload_polls_until_every_task_is_done() {
+    let mut harness = Harness::logged_in();
+
+    harness.upload("sample.exe", b"MZ");
+    assert_eq!(harness.last_request("/v1/reports/create").method, "POST");
+    assert_eq!(harness.last_request("/v1/reports/create").body, b"MZ");
+
+    harness.respond("/v1/reports/create", 200, r#"{"report_id": 7}"#);
+    assert_eq!(harness.model.state.report_id, Some(7));
+    assert!(harness.model.is_polling());
+
+    harness.respond("/v1/reports/7/tasks", 200, &tasks_json(&["pending", "new"]));
+    assert!(harness.model.is_polling());
+
+    harness.send(Msg::PollReport(7));
+    harness.respond("/v1/reports/7/status", 200, r#"{"status": "complete"}"#);
+    harness.respond(
+        "/v1/reports/7/tasks",
+        200,
+        &tasks_json(&["clean", "infected"]),
+    );
+
+    assert!(!harness.model.is_polling());
+    assert!(!harness.is_in_flight("/tasks"));
+    assert_eq!(harness.model.state.report_id, None);
+    assert!(harness.model.is_scan_complete());
+    assert_eq!(
+        harness.model.current_pending_tasks.as_ref().unwrap().len(),
+        2
+    );
+}
+
+#[test]
+fn purged_report_stops_polling() {
+    let mut harness = Harness::restored(&[(KEY, r#"{"token": "t0k", "report_id": 7}"#)]);
+    harness.configure(CONFIG);
+    harness.respond("/v1/profiles", 200, PROFILES);
+    assert!(harness.model.is_polling());
+
+    harness.respond("/v1/reports/7/tasks", 404, "");
+    assert!(harness.model.is_report_unavailable);
+    assert!(!harness.model.is_polling());
+    assert_eq!(harness.model.state.report_id, None);
+    let stored: State = serde_json::from_str(&harness.stored(KEY).unwrap()).unwrap();
+    assert_eq!(stored.report_id, None);
+}
+
+#[test]
+fn normalize_api_url_cleans_up_or_rejects() {
+    assert_eq!(
+        normalize_api_url("https://api.test").unwrap(),
+        "https://api.test"
+    );
+    assert_eq!(
+        normalize_api_url("https://api.test//").unwrap(),
+        "https://api.test"
+    );
+    assert_eq!(
+        normalize_api_url(" http://api.test/ \n").unwrap(),
+        "http://api.test"
+    );
+    assert!(normalize_api_url("api.test").is_err());
+    assert!(normalize_api_url("ftp://api.test").is_err());
+    assert!(normalize_api_url("https://").is_err());
+    assert!(normalize_api_url("https://api .test").is_err());
+}
+
+#[test]
+fn invalid_api_url_shows_the_config_error() {
+    let harness = Harness::with_config(r#"{"api_url": "api.test"}"#);
+    assert_eq!(harness.model.scene, Scene::FetchConfigError);
+    assert!(harness.model.config.is_none());
+}
+
+#[test]
+fn report_without_tasks_explains_why() {
+    let mut harness = Harness::logged_in();
+    harness.upload("sample.exe", b"MZ");
+    harness.respond("/v1/reports/create", 200, r#"{"report_id": 7}"#);
+    assert!(harness.model.current_pending_tasks.is_none());
+
+    harness.respond("/v1/reports/7/tasks", 200, r#"{"tasks": []}"#);
+    assert!(!harness.model.is_polling());
+    assert_eq!(
+        harness.model.current_pending_tasks.as_ref().map(Vec::len),
+        Some(0)
+    );
+    assert!(harness
+        .rendered_text()
+        .contains("No engines ran for this report"));
+}
+
+#[test]
+fn guest_login_without_token_is_an_error() {
+    let mut harness =
+        Harness::with_config(r#"{"api_url": "http://api.test", "allow_anonymous": true}"#);
+    harness.send(Msg::GuestLogin);
+    harness.respond("/v1/auth/guest", 200, "{}");
+
+    assert!(!harness.model.is_guest);
+    assert!(!harness.model.is_guest_login_loading);
+    assert_eq!(harness.model.state.token, None);
+    assert_eq!(harness.model.scene, Scene::LoginRegister);
+    assert_eq!(
+        harness.model.loginregister_error.as_deref(),
+        Some("Could not continue as guest")
+    );
+}
+
+#[test]
+fn guest_login_fetches_profiles() {
+    let mut harness =
+        Harness::with_config(r#"{"api_url": "http://api.test", "allow_anonymous": true}"#);
+    harness.send(Msg::GuestLogin);
+    harness.respond("/v1/auth/guest", 200, r#"{"token": "gu3st"}"#);
+    harness.respond("/v1/profiles", 200, PROFILES);
+
+    assert!(harness.model.is_guest);
+    assert_eq!(harness.model.scene, Scene::LoggedIn);
+    assert!(!harness.stored(KEY).unwrap_or_default().contains("gu3st"));
+}
+
+fn config(json: &str) -> Config {
+    serde_json::from_str(json).unwrap()
+}
+
+#[test]
+fn feature_flags_default_to_off() {
+    let flags = config(r#"{"api_url": "", "features": {"export": true, "history": false}}"#);
+    assert!(flags.feature_enabled("export"));
+    assert!(!flags.feature_enabled("history"));
+    assert!(!flags.feature_enabled("unknown"));
+    assert!(!config(r#"{"api_url": ""}"#).feature_enabled("export"));
+}
+
+#[test]
+fn feature_flags_override_the_older_config_fields() {
+    let legacy = config(r#"{"api_url": "", "url_scanning_enabled": true}"#);
+    assert!(legacy.feature_enabled("url_scanning"));
+
+    let overridden = config(
+        r#"{"api_url": "", "url_scanning_enabled": true, "features": {"url_scanning": false}}"#,
+    );
+    assert!(!overridden.feature_enabled("url_scanning"));
+}
+
+#[test]
+fn selection_survives_a_renamed_machine_name() {
+    let mut harness = Harness::restored(&[
+        (KEY, r#"{"token": "t0k"}"#),
+        (
+            SETTINGS_KEY,
+            r#"{"enabled_profile_ids": [2], "known_profile_ids": [1, 2]}"#,
+        ),
+    ]);
+    harness.configure(CONFIG);
+    harness.respond(
+        "/v1/profiles",
+        200,
+        &PROFILES.replace(r#""machine_name": "yara""#, r#""machine_name": "yara_v4""#),
+    );
+
+    assert_eq!(
+        harness.model.enabled_profiles,
+        [2].iter().cloned().collect()
+    );
+    assert_eq!(
+        harness.model.enabled_machine_names(),
+        ["yara_v4"].iter().cloned().collect()
+    );
+    let stored: Settings = serde_json::from_str(&harness.stored(SETTINGS_KEY).unwrap()).unwrap();
+    assert_eq!(
+        stored.enabled_profile_ids,
+        Some([2].iter().cloned().collect())
+    );
+}
+
+/// Logged in with report 7 uploaded and its first tasks response answered.
+fn polling(statuses: &[&str]) -> Harness {
+    let mut harness = Harness::logged_in();
+    harness.upload("sample.exe", b"MZ");
+    harness.respond("/v1/reports/create", 200, r#"{"report_id": 7}"#);
+    harness.respond("/v1/reports/7/tasks", 200, &tasks_json(statuses));
+    harness
+}
+
+#[test]
+fn polling_gives_up_on_tasks_that_never_finish() {
+    let mut harness = polling(&["pending", "clean"]);
+    harness.model.poll_attempts = MAX_POLL_ATTEMPTS - 1;
+
+    harness.send(Msg::PollReport(7));
+    harness.respond("/v1/reports/7/status", 200, r#"{"status": "pending"}"#);
+    harness.respond(
+        "/v1/reports/7/tasks",
+        200,
+        &tasks_json(&["pending", "clean"]),
+    );
+
+    assert!(!harness.model.is_polling());
+    assert_eq!(
+        harness.model.timed_out_task_ids,
+        [10].iter().cloned().collect()
+    );
+    let text = harness.rendered_text();
+    assert!(text.contains("Timed out waiting"));
+    assert!(text.contains("Keep waiting"));
+
+    harness.send(Msg::KeepWaiting);
+    assert!(harness.model.is_polling());
+    assert!(harness.model.timed_out_task_ids.is_empty());
+    assert_eq!(harness.model.poll_attempts, 0);
+}
+
+fn report(id: i64, created_when: &str, file_multihash: &str) -> Report {
+    Report {
+        id,
+        user_id: 1,
+        created_when: created_when.parse().unwrap(),
+        file_multihash: file_multihash.into(),
+    }
+}
+
+#[test]
+fn filter_reports_by_hash_and_date() {
+    let reports = vec![
+        report(1, "2020-01-01T10:00:00Z", "1220abcdef"),
+        report(2, "2020-01-15T23:59:59Z", "1220ABC123"),
+        report(3, "2020-02-01T00:00:00Z", "12209999ff"),
+    ];
+    let cases = [
+        ("", None, None, &[1, 2, 3][..]),
+        ("abc", None, None, &[1, 2]),
+        (" ABCDEF ", None, None, &[1]),
+        ("nothing", None, None, &[]),
+        ("", Some("2020-01-15"), None, &[2, 3]),
+        ("", None, Some("2020-01-15"), &[1, 2]),
+        ("", Some("2020-01-02"), Some("2020-01-31"), &[2]),
+        ("abc", Some("2020-01-02"), None, &[2]),
+        ("", Some("2020-02-02"), Some("2020-01-01"), &[]),
+    ];
+    for (hash_query, from, to, expected) in cases.iter() {
+        let date = |x: &Option<&str>| x.map(|x| x.parse::<NaiveDate>().unwrap());
+        let ids: Vec<i64> = filter_reports(&reports, hash_query, date(from), date(to))
+            .iter()
+            .map(|x| x.id)
+            .collect();
+        assert_eq!(
+            &ids[..],
+            *expected,
+            "{:?} from {:?} to {:?}",
+            hash_query,
+            from,
+            to
+        );
+    }
+}
+
+#[test]
+fn malformed_profiles_are_a_decode_error() {
+    for body in &[
+        "",
+        "{",
+        r#"{"profiles": {}}"#,
+        r#"{"profiles": [{"id": "1"}]}"#,
+    ] {
+        let error = parse_profiles_response(body).err().unwrap();
+        assert!(
+            error.downcast_ref::<serde_json::Error>().is_some(),
+            "{:?}",
+            body
+        );
+    }
+    assert_eq!(parse_profiles_response(PROFILES).unwrap().profiles.len(), 2);
+}
+
+#[test]
+fn malformed_profiles_show_the_detail_in_debug_mode_only() {
+    let mut harness = Harness::restored(&[(KEY, r#"{"token": "t0k"}"#)]);
+    harness.configure(CONFIG);
+    harness.respond("/v1/profiles", 200, r#"{"profiles": 1}"#);
+    assert_eq!(
+        harness.model.fetch_profiles_error.as_deref(),
+        Some("Unexpected response from server")
+    );
+
+    let mut harness = Harness::restored(&[(KEY, r#"{"token": "t0k"}"#)]);
+    harness.configure(r#"{"api_url": "http://api.test", "debug": true}"#);
+    harness.respond("/v1/profiles", 200, r#"{"profiles": 1}"#);
+    let error = harness.model.fetch_profiles_error.unwrap();
+    assert!(
+        error.starts_with("Unexpected response from server: invalid type"),
+        "{}",
+        error
+    );
+
+    let mut harness = Harness::restored(&[(KEY, r#"{"token": "t0k"}"#)]);
+    harness.configure(CONFIG);
+    harness.respond("/v1/profiles", 400, "");
+    assert_eq!(
+        harness.model.fetch_profiles_error.as_deref(),
+        Some("Could not fetch profiles")
+    );
+}
+
+fn task(profile_id: i64, status: &str) -> Task {
+    Task {
+        id: 10 + profile_id,
+        report_id: 7,
+        profile_id,
+        created_when: "2020-01-01T00:00:00Z".parse().unwrap(),
+        completed_when: None,
+        status: status.into(),
+        message: None,
+        queue_position: None,
+    }
+}
+
+#[test]
+fn changed_verdicts_compares_final_results_only() {
+    let previous: HashMap<i64, String> = vec![
+        (1, "clean".to_owned()),
+        (2, "detected".to_owned()),
+        (3, "clean".to_owned()),
+    ]
+    .into_iter()
+    .collect();
+    let tasks = vec![
+        task(1, "detected"),
+        task(2, "detected"),
+        task(3, "pending"),
+        task(4, "clean"),
+    ];
+
+    // Unchanged, still running and never scanned before are all left out.
+    assert_eq!(
+        changed_verdicts(&previous, &tasks),
+        [1].iter().cloned().collect()
+    );
+    assert!(changed_verdicts(&HashMap::new(), &tasks).is_empty());
+}
+
+#[test]
+fn to_curl_quotes_every_argument() {
+    let token = Some("t0k'en".to_owned());
+    let request = RecordedRequest::new(
+        "GET",
+        "http://api.test/v1/reports?hash=12 20&x='y'",
+        &token,
+        RequestBody::Empty,
+    );
+    assert_eq!(
+        request.to_curl(false),
+        r#"curl -X GET 'http://api.test/v1/reports?hash=12 20&x='\''y'\''' -H 'Authorization: <token>'"#
+    );
+    assert_eq!(
+        request.to_curl(true),
+        r#"curl -X GET 'http://api.test/v1/reports?hash=12 20&x='\''y'\''' -H 'Authorization: t0k'\''en'"#
+    );
+}
+
+#[test]
+fn to_curl_describes_the_body() {
+    let json = RecordedRequest::new(
+        "POST",
+        "http://api.test/v1/reports",
+        &None,
+        RequestBody::Json,
+    );
+    assert_eq!(
+        json.to_curl(true),
+        "curl -X POST 'http://api.test/v1/reports' -H 'Content-Type: application/json' --data '@request.json'"
+    );
+
+    let binary = RecordedRequest::new(
+        "POST",
+        "http://api.test/v1/reports",
+        &None,
+        RequestBody::Binary,
+    );
+    assert_eq!(
+        binary.to_curl(true),
+        "curl -X POST 'http://api.test/v1/reports' --data-binary '@file'"
+    );
+}
+
+#[test]
+fn only_recommended_profiles_are_enabled_by_default() {
+    let profiles = PROFILES.replace(
+        r#""module": "clamav","#,
+        r#""module": "clamav", "recommended": true,"#,
+    );
+    let mut harness = Harness::logged_in_with(CONFIG, &profiles);
+    assert_eq!(
+        harness.model.enabled_profiles,
+        [1].iter().cloned().collect()
+    );
+
+    harness.send(Msg::SelectAllProfiles);
+    assert_eq!(
+        harness.model.enabled_profiles,
+        [1, 2].iter().cloned().collect()
+    );
+}
+
+#[test]
+fn truncate_pretty_json_at_the_limit() {
+    let pretty = "{\n  \"a\": 1,\n  \"b\": 2\n}";
+    assert_eq!(truncate_pretty_json(pretty, pretty.len()), None);
+    assert_eq!(
+        truncate_pretty_json(pretty, pretty.len() - 1),
+        Some("{\n  \"a\": 1,\n  \"b\": 2")
+    );
+    assert_eq!(truncate_pretty_json(pretty, 12), Some("{\n  \"a\": 1,"));
+    // A single line longer than the limit is cut inside the line.
+    assert_eq!(truncate_pretty_json("[1, 2, 3]", 4), Some("[1, "));
+}
+
+#[test]
+fn truncate_pretty_json_never_splits_a_character() {
+    // "é" takes bytes 1 and 2, "€" bytes 3 to 5.
+    let pretty = "\"é€\"";
+    assert_eq!(truncate_pretty_json(pretty, 2), Some("\""));
+    assert_eq!(truncate_pretty_json(pretty, 3), Some("\"é"));
+    assert_eq!(truncate_pretty_json(pretty, 5), Some("\"é"));
+    assert_eq!(truncate_pretty_json(pretty, 6), Some("\"é€"));
+    assert_eq!(truncate_pretty_json(pretty, 7), None);
+}
+
+#[test]
+fn status_rollup_fetches_tasks_only_once_complete() {
+    let mut harness = polling(&["pending", "pending"]);
+
+    harness.send(Msg::PollReport(7));
+    harness.respond("/v1/reports/7/status", 200, r#"{"status": "pending"}"#);
+    assert!(!harness.is_in_flight("/tasks"));
+    assert!(harness.model.is_polling());
+
+    harness.send(Msg::PollReport(7));
+    harness.respond("/v1/reports/7/status", 200, r#"{"status": "complete"}"#);
+    harness.respond(
+        "/v1/reports/7/tasks",
+        200,
+        &tasks_json(&["clean", "detected"]),
+    );
+    assert!(!harness.model.is_polling());
+    assert!(harness.model.it.is_none());
+}
+
+#[test]
+fn missing_status_rollup_falls_back_to_tasks() {
+    let mut harness = polling(&["pending"]);
+
+    harness.send(Msg::PollReport(7));
+    harness.respond("/v1/reports/7/status", 404, "");
+    assert!(harness.model.is_status_rollup_unavailable);
+    harness.respond("/v1/reports/7/tasks", 200, &tasks_json(&["pending"]));
+
+    harness.send(Msg::PollReport(7));
+    assert!(!harness.is_in_flight("/status"));
+    harness.respond("/v1/reports/7/tasks", 200, &tasks_json(&["clean"]));
+    assert!(!harness.model.is_polling());
+}
+
+#[test]
+fn poll_interval_follows_the_connection_type() {
+    assert_eq!(poll_interval_for_connection(Some("slow-2g")), Some(5000));
+    assert_eq!(poll_interval_for_connection(Some("2g")), Some(3000));
+    assert_eq!(poll_interval_for_connection(Some("3g")), Some(2000));
+    assert_eq!(poll_interval_for_connection(Some("4g")), Some(1000));
+    assert_eq!(poll_interval_for_connection(Some("5g")), None);
+    assert_eq!(poll_interval_for_connection(None), None);
+}
+
+#[test]
+fn configured_poll_interval_wins() {
+    let harness =
+        Harness::with_config(r#"{"api_url": "http://api.test", "poll_interval_ms": 250}"#);
+    assert_eq!(harness.model.poll_interval_ms, 250);
+
+    // Natively there is no Network Information API to ask.
+    let harness = Harness::with_config(CONFIG);
+    assert_eq!(harness.model.poll_interval_ms, DEFAULT_POLL_INTERVAL_MS);
+}
+
+#[test]
+fn scan_phase_follows_the_upload() {
+    let mut harness = Harness::logged_in();
+    assert!(harness.model.scan_phase().is_none());
+
+    harness.model.is_file_uploading = true;
+    assert!(harness.model.scan_phase() == Some(ScanPhase::Reading));
+    harness.upload("sample.exe", b"MZ");
+    assert!(harness.model.scan_phase() == Some(ScanPhase::Uploading));
+    harness.respond("/v1/reports/create", 200, r#"{"report_id": 7}"#);
+    assert!(harness.model.scan_phase() == Some(ScanPhase::Scanning));
+    harness.respond("/v1/reports/7/tasks", 200, &tasks_json(&["clean"]));
+    assert!(harness.model.scan_phase() == Some(ScanPhase::Done));
+}
+
+#[cfg(feature = "pdf_export")]
+#[test]
+fn pdf_reports_split_into_pages() {
+    // 60 lines take two pages of 50.
+    let lines: Vec<String> = (0..60).map(|x| format!("Engine {}: clean", x)).collect();
+    let pdf = pdf::render(&lines);
+    // The header comment carries binary bytes so transfer tools treat the file as binary.
+    let pdf = String::from_utf8_lossy(&pdf);
+
+    assert!(pdf.starts_with("%PDF-"));
+    assert!(pdf.trim_end().ends_with("%%EOF"));
+    assert!(pdf.contains("/Count 2"));
+    assert!(pdf.contains("/BaseFont /Helvetica"));
+    assert!(pdf.contains("/Encoding /WinAnsiEncoding"));
+    assert!(pdf.contains("(Engine 49: clean) Tj"));
+    assert!(pdf.contains("(Engine 59: clean) Tj"));
+}
+
+#[cfg(feature = "pdf_export")]
+#[test]
+fn pdf_text_is_encoded_for_the_standard_fonts() {
+    assert_eq!(
+        pdf::encode_win_ansi("a (b) café 5 € — 漢"),
+        b"a (b) caf\xe9 5 \x80 \x97 ?".to_vec()
+    );
+
+    let pdf = pdf::render(&["a (b) \\ c".to_owned(), "café".to_owned()]);
+    let pdf = String::from_utf8_lossy(&pdf);
+    assert!(pdf.contains(r"(a (b) \\ c) Tj"));
+    assert!(pdf.contains("<636166E9> Tj"));
+}
+
+#[test]
+fn encode_base64_pads_the_last_group() {
+    assert_eq!(encode_base64(b""), "");
+    assert_eq!(encode_base64(b"f"), "Zg==");
+    assert_eq!(encode_base64(b"fo"), "Zm8=");
+    assert_eq!(encode_base64(b"foo"), "Zm9v");
+    assert_eq!(encode_base64(b"foobar"), "Zm9vYmFy");
+    assert_eq!(encode_base64(&[0xff, 0xfe, 0x00]), "//4A");
+}
+
+#[test]
+fn upload_without_overrides_sends_the_raw_file() {
+    let mut harness = Harness::logged_in();
+    harness.upload("sample.exe", b"MZ");
+
+    let request = harness.last_request("/v1/reports/create");
+    assert_eq!(request.body, b"MZ");
+    assert_eq!(request.header("Content-Type"), None);
+    assert_eq!(request.header("X-Profile-Config-Overrides"), None);
+}
+
+#[test]
+fn upload_with_overrides_sends_them_in_a_json_body() {
+    let mut harness = Harness::logged_in();
+    harness
+        .model
+        .config_overrides
+        .insert("yara".into(), serde_json::json!({"rules": "strict"}));
+    // Overrides of disabled engines stay out of the request.
+    harness
+        .model
+        .config_overrides
+        .insert("other".into(), serde_json::json!({}));
+    harness.upload("sample.exe", b"MZ");
+
+    let request = harness.last_request("/v1/reports/create");
+    assert_eq!(request.header("Content-Type"), Some("application/json"));
+    let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+    assert_eq!(
+        body,
+        serde_json::json!({"file": "TVo=", "overrides": {"yara": {"rules": "strict"}}})
+    );
+}
+
+#[test]
+fn classify_create_report_rejection_by_status() {
+    let classify = |status: u16, body: &str| {
+        classify_create_report_rejection(StatusCode::from_u16(status).unwrap(), body)
+    };
+
+    assert!(matches!(
+        classify(413, ""),
+        Some(CreateReportRejection::TooLarge)
+    ));
+    match classify(429, r#"{"used": 3, "limit": 5}"#) {
+        Some(CreateReportRejection::QuotaExceeded(usage)) => {
+            assert_eq!((usage.used, usage.limit), (Some(3), Some(5)))
+        }
+        other => panic!("{:?}", other),
+    }
+    match classify(402, "quota exceeded") {
+        Some(CreateReportRejection::QuotaExceeded(usage)) => {
+            assert_eq!((usage.used, usage.limit), (None, None))
+        }
+        other => panic!("{:?}", other),
+    }
+    assert!(classify(400, "").is_none());
+    assert!(classify(500, "").is_none());
+}
+
+#[test]
+fn oversized_upload_explains_the_limit() {
+    let mut harness = Harness::logged_in();
+    harness.upload("huge.iso", b"MZ");
+    harness.respond("/v1/reports/create", 413, "");
+
+    assert!(!harness.model.is_file_uploading);
+    assert_eq!(harness.model.create_report_error, None);
+    let text = harness.rendered_text();
+    assert!(text.contains("File too large"));
+    assert!(text.contains("Try compressing the file"));
+}
+
+#[test]
+fn exhausted_quota_shows_the_usage() {
+    let mut harness = Harness::logged_in();
+    harness.upload("sample.exe", b"MZ");
+    harness.respond("/v1/reports/create", 429, r#"{"used": 10, "limit": 10}"#);
+
+    let text = harness.rendered_text();
+    assert!(text.contains("Scan quota exceeded"));
+    assert!(text.contains("You have used 10 of your 10 scans."));
+    assert!(text.contains("upgrade your plan"));
+
+    // Any other refusal keeps the generic error.
+    harness.upload("sample.exe", b"MZ");
+    assert!(harness.model.create_report_rejection.is_none());
+    harness.respond("/v1/reports/create", 400, "");
+    assert!(harness.model.create_report_rejection.is_none());
+    assert_eq!(
+        harness.model.create_report_error.as_deref(),
+        Some("Could not create report")
+    );
+}
+
+#[test]
+fn request_builder_adds_the_extra_headers() {
+    let config = config(
+        r#"{"api_url": "http://api.test", "extra_headers": {
+            "X-Tenant-Id": "acme",
+            "authorization": "Basic nope",
+            "Content-Type": "text/plain",
+            "X-Request-Id": "spoofed",
+            "Bad Name": "x",
+            "X-Bad-Value": "a\nb"
+        }}"#,
+    );
+    let request = request_builder(&config, "GET", "http://api.test/v1/profiles", "abcd")
+        .header("Authorization", "t0k")
+        .body(())
+        .unwrap();
+
+    let headers = request.headers();
+    assert_eq!(headers["X-Tenant-Id"], "acme");
+    assert_eq!(headers["X-Request-Id"], "abcd");
+    assert_eq!(
+        headers.get_all("Authorization").iter().collect::<Vec<_>>(),
+        ["t0k"]
+    );
+    assert!(headers.get("Content-Type").is_none());
+    assert_eq!(headers.len(), 3);
+}
+
+#[test]
+fn extra_headers_reach_every_request() {
+    let harness = Harness::logged_in_with(
+        r#"{"api_url": "http://api.test", "extra_headers": {"X-Tenant-Id": "acme"}}"#,
+        PROFILES,
+    );
+    let requests = &harness.model.fetch_service.requests;
+    assert!(requests.len() >= 2);
+    for request in requests {
+        assert_eq!(
+            request.header("X-Tenant-Id"),
+            Some("acme"),
+            "{}",
+            request.uri
+        );
+    }
+}
+
+#[test]
+fn server_errors_while_polling_keep_the_session() {
+    let mut harness = polling(&["pending"]);
+
+    for _ in 0..MAX_RECONNECT_ATTEMPTS {
+        harness.send(Msg::PollReport(7));
+        harness.respond("/v1/reports/7/status", 503, "");
+        assert!(harness.model.is_reconnecting);
+        assert!(harness.model.is_polling());
+        assert_eq!(harness.model.scene, Scene::LoggedIn);
+        assert_eq!(harness.model.state.token.as_deref(), Some("t0k"));
+    }
+
+    harness.send(Msg::PollReport(7));
+    harness.respond("/v1/reports/7/status", 503, "");
+    assert!(!harness.model.is_reconnecting);
+    assert!(!harness.model.is_polling());
+    assert!(harness.model.is_connection_lost);
+    assert_eq!(harness.model.scene, Scene::LoggedIn);
+}
+
+#[test]
+fn reconnecting_ends_with_the_next_answer() {
+    let mut harness = polling(&["pending"]);
+    harness.send(Msg::PollReport(7));
+    harness.respond("/v1/reports/7/status", 503, "");
+    assert!(harness.model.is_reconnecting);
+
+    harness.send(Msg::PollReport(7));
+    harness.respond("/v1/reports/7/status", 200, r#"{"status": "pending"}"#);
+    assert!(!harness.model.is_reconnecting);
+    assert_eq!(harness.model.reconnect_attempts, 0);
+}
+
+#[test]
+fn unauthorized_while_polling_ends_the_session() {
+    let mut harness = polling(&["pending"]);
+
+    harness.send(Msg::PollReport(7));
+    harness.respond("/v1/reports/7/status", 401, "");
+    assert_eq!(
+        harness.last_request("/v1/auth/refresh").body_text(),
+        r#"{"refresh_token":"r3f"}"#
+    );
+
+    harness.respond("/v1/auth/refresh", 401, "");
+    assert_eq!(harness.model.scene, Scene::LoginRegister);
+    assert_eq!(harness.model.state.token, None);
+    assert!(!harness.model.is_polling());
+    assert_eq!(
+        harness.model.modal.as_ref().map(|x| x.title.as_str()),
+        Some("Session expired")
+    );
+}
+
+#[test]
+fn audit_log_to_csv_quotes_actions() {
+    let timestamp: DateTime<Utc> = "2020-01-01T10:00:00Z".parse().unwrap();
+    let entries = vec![
+        AuditEntry {
+            timestamp,
+            action: "Logged in as alice".into(),
+        },
+        AuditEntry {
+            timestamp,
+            action: "Uploaded a, \"b\".exe\nas report #7".into(),
+        },
+    ];
+
+    assert_eq!(
+        audit_log_to_csv(&entries),
+        "timestamp,action\n\
+         2020-01-01T10:00:00+00:00,\"Logged in as alice\"\n\
+         2020-01-01T10:00:00+00:00,\"Uploaded a, \"\"b\"\".exe\nas report #7\"\n"
+    );
+    assert_eq!(audit_log_to_csv(&[]), "timestamp,action\n");
+}
+
+#[test]
+fn audit_log_records_the_session() {
+    let mut harness = polling(&["clean"]);
+    let actions: Vec<&str> = harness
+        .model
+        .audit_log
+        .iter()
+        .map(|x| x.action.as_str())
+        .collect();
+    assert_eq!(
+        actions,
+        [
+            "Logged in as alice",
+            "Uploaded sample.exe as report #7",
+            "Report #7 completed: Clean",
+        ]
+    );
+
+    harness.send(Msg::Logout);
+    harness.respond("/v1/auth/logout", 204, "");
+    assert!(harness.model.audit_log.is_empty());
+}
+
+/// Logged in from storage with `settings`, and `PROFILES` fetched.
+fn restored_with_settings(settings: &str) -> Harness {
+    let mut harness = Harness::restored(&[(KEY, r#"{"token": "t0k"}"#), (SETTINGS_KEY, settings)]);
+    harness.configure(CONFIG);
+    harness.respond("/v1/profiles", 200, PROFILES);
+    harness
+}
+
+#[test]
+fn new_profiles_start_disabled_by_default() {
+    let mut harness =
+        restored_with_settings(r#"{"enabled_profile_ids": [1], "known_profile_ids": [1]}"#);
+    assert_eq!(
+        harness.model.enabled_profiles,
+        [1].iter().cloned().collect()
+    );
+    assert_eq!(
+        harness.model.settings.known_profile_ids,
+        Some([1, 2].iter().cloned().collect())
+    );
+
+    // Once known, the profile is not treated as new on the next refresh either.
+    harness.send(Msg::ToggleAutoEnableNewProfiles);
+    harness.send(Msg::FetchProfiles);
+    harness.respond("/v1/profiles", 200, PROFILES);
+    assert_eq!(
+        harness.model.enabled_profiles,
+        [1].iter().cloned().collect()
+    );
+}
+
+#[test]
+fn new_profiles_can_be_enabled_automatically() {
+    let harness = restored_with_settings(
+        r#"{"enabled_profile_ids": [1], "known_profile_ids": [1], "auto_enable_new_profiles": true}"#,
+    );
+    assert_eq!(
+        harness.model.enabled_profiles,
+        [1, 2].iter().cloned().collect()
+    );
+}
+
+const DUPLICATE_PROFILES: &str = r#"{"profiles": [
+    {"id": 1, "machine_name": "clamav", "human_name": "ClamAV", "module": "clamav", "config": null},
+    {"id": 2, "machine_name": "yara", "human_name": "YARA", "module": "yara", "config": null},
+    {"id": 3, "machine_name": "clamav", "human_name": "ClamAV (beta)", "module": "clamav", "config": null}
+]}"#;
+
+#[test]
+fn duplicate_machine_names_are_listed_once() {
+    let profiles = parse_profiles_response(DUPLICATE_PROFILES)
+        .unwrap()
+        .profiles;
+    assert_eq!(duplicate_machine_names(&profiles), ["clamav"]);
+    assert!(duplicate_machine_names(&profiles[..2]).is_empty());
+}
+
+#[test]
+fn profiles_sharing_a_machine_name_toggle_independently() {
+    let mut harness = Harness::logged_in_with(CONFIG, DUPLICATE_PROFILES);
+    assert_eq!(
+        harness.model.enabled_profiles,
+        [1, 2, 3].iter().cloned().collect()
+    );
+
+    harness.send(Msg::ToggleProfile(3));
+    assert_eq!(
+        harness.model.enabled_profiles,
+        [1, 2].iter().cloned().collect()
+    );
+    harness.send(Msg::ToggleProfile(3));
+    harness.send(Msg::ToggleProfile(1));
+    assert_eq!(
+        harness.model.enabled_profiles,
+        [2, 3].iter().cloned().collect()
+    );
+}
+
+#[test]
+fn queued_tasks_show_their_position() {
+    let tasks = tasks_json(&["new", "new"]).replacen(
+        r#""status": "new""#,
+        r#""status": "new", "queue_position": 4"#,
+        1,
+    );
+    let mut harness = Harness::logged_in();
+    harness.upload("sample.exe", b"MZ");
+    harness.respond("/v1/reports/create", 200, r#"{"report_id": 7}"#);
+    harness.respond("/v1/reports/7/tasks", 200, &tasks);
+
+    let text = harness.rendered_text();
+    assert!(text.contains("Queued (#4)"));
+    assert!(text.contains("Waiting for worker.."));
+}
+
+#[test]
+fn polls_yield_to_a_request_in_flight() {
+    let mut harness = polling(&["pending"]);
+    harness.send(Msg::FetchProfiles);
+
+    harness.send(Msg::PollReport(7));
+    harness.send(Msg::FetchTasks(7));
+    assert!(harness.is_in_flight("/v1/profiles"));
+    assert!(!harness.is_in_flight("/status"));
+    assert!(!harness.is_in_flight("/tasks"));
+
+    harness.respond("/v1/profiles", 200, PROFILES);
+    harness.send(Msg::PollReport(7));
+    assert!(harness.is_in_flight("/v1/reports/7/status"));
+}
+
+#[test]
+fn replacing_a_request_in_flight_drops_its_answer() {
+    let mut harness = Harness::logged_in();
+    harness.send(Msg::FetchProfiles);
+    harness.send(Msg::Logout);
+
+    assert!(!harness.is_in_flight("/v1/profiles"));
+    assert_eq!(harness.model.ft_label, "Logout");
+    harness.respond("/v1/auth/logout", 204, "");
+    assert_eq!(harness.model.scene, Scene::LoginRegister);
+}
+
+#[test]
+fn threat_lookup_url_encodes_the_query() {
+    let template = "https://intel.test/search?q={query}&src=violetear";
+    assert_eq!(
+        threat_lookup_url(template, "Win.Trojan.Agent-123"),
+        Some("https://intel.test/search?q=Win.Trojan.Agent-123&src=violetear".into())
+    );
+    assert_eq!(
+        threat_lookup_url(template, "a b&c=d#e/../f?g"),
+        Some("https://intel.test/search?q=a%20b%26c%3Dd%23e%2F..%2Ff%3Fg&src=violetear".into())
+    );
+    assert_eq!(
+        threat_lookup_url(template, "Eicar-Test-Signature ☣"),
+        Some("https://intel.test/search?q=Eicar-Test-Signature%20%E2%98%A3&src=violetear".into())
+    );
+    assert_eq!(
+        threat_lookup_url("https://intel.test/{query}", "x"),
+        Some("https://intel.test/x".into())
+    );
+}
+
+#[test]
+fn threat_lookup_url_ignores_unusable_templates() {
+    assert_eq!(threat_lookup_url("https://intel.test/search", "x"), None);
+    assert_eq!(threat_lookup_url("javascript:alert('{query}')", "x"), None);
+    assert_eq!(threat_lookup_url("{query}", "x"), None);
+}
+
+#[test]
+fn parse_logout_response_reads_error_bodies() {
+    let parse = |status: u16, body: &str| {
+        parse_logout_response(StatusCode::from_u16(status).unwrap(), Ok(body.to_owned()))
+            .map_err(|x| x.to_string())
+    };
+
+    assert_eq!(parse(204, ""), Ok(()));
+    assert_eq!(parse(200, "{}"), Ok(()));
+    assert_eq!(
+        parse(200, r#"{"error": "token revoked"}"#),
+        Err("token revoked".into())
+    );
+    assert_eq!(
+        parse(500, r#"{"message": "database down"}"#),
+        Err("database down".into())
+    );
+    assert_eq!(parse(502, "<html>"), Err("502 Bad Gateway".into()));
+}
+
+#[test]
+fn failed_logout_still_clears_the_session() {
+    let mut harness = Harness::logged_in();
+    harness.send(Msg::Logout);
+    assert!(harness.model.is_logout_loading);
+
+    harness.respond("/v1/auth/logout", 500, r#"{"error": "database down"}"#);
+    assert!(!harness.model.is_logout_loading);
+    assert_eq!(harness.model.scene, Scene::LoginRegister);
+    assert_eq!(harness.model.state.token, None);
+    assert_eq!(
+        harness.model.logout_warning.as_deref(),
+        Some("Logged out here, but the server reported an error: database down")
+    );
+    assert!(harness
+        .rendered_text()
+        .contains("the server reported an error"));
+}
+
+#[test]
+fn reconcile_last_results_follows_the_fetched_profiles() {
+    let profiles = parse_profiles_response(PROFILES).unwrap().profiles;
+    let last_results: HashMap<i64, String> =
+        vec![(1, "detected".to_owned()), (9, "clean".to_owned())]
+            .into_iter()
+            .collect();
+
+    // Profile 9 was removed, profile 2 is new and has no result yet.
+    let reconciled = reconcile_last_results(&profiles, &last_results);
+    assert_eq!(
+        reconciled,
+        vec![(1, "detected".to_owned())].into_iter().collect()
+    );
+    assert!(reconcile_last_results(&profiles, &HashMap::new()).is_empty());
+}
+
+#[test]
+fn last_results_are_remembered_across_sessions() {
+    let mut harness = restored_with_settings(r#"{"last_results": {"1": "clean", "9": "clean"}}"#);
+    assert_eq!(harness.model.settings.last_results.len(), 1);
+
+    harness.upload("sample.exe", b"MZ");
+    harness.respond("/v1/reports/create", 200, r#"{"report_id": 7}"#);
+    harness.respond(
+        "/v1/reports/7/tasks",
+        200,
+        &tasks_json(&["detected", "pending"]),
+    );
+
+    let stored: Settings = serde_json::from_str(&harness.stored(SETTINGS_KEY).unwrap()).unwrap();
+    assert_eq!(
+        stored.last_results,
+        vec![(1, "detected".to_owned())].into_iter().collect()
+    );
+}
+
+#[test]
+fn scan_score_counts_detections() {
+    assert_eq!(scan_score(&[]), None);
+    assert_eq!(scan_score(&[task(1, "clean")]), Some((0, 1)));
+    assert_eq!(
+        scan_score(&[task(1, "detected"), task(2, "clean"), task(3, "error")]),
+        Some((1, 3))
+    );
+    assert_eq!(
+        scan_score(&[task(1, "detected"), task(2, "detected")]),
+        Some((2, 2))
+    );
+}
+
+#[test]
+fn scan_score_class_thresholds() {
+    assert!(scan_score_class(0, 40).contains("is-success"));
+    assert!(scan_score_class(1, 40).contains("is-warning"));
+    assert!(scan_score_class(4, 40).contains("is-warning"));
+    assert!(scan_score_class(5, 40).contains("is-danger"));
+    assert!(scan_score_class(1, 1).contains("is-danger"));
+}
+
+#[test]
+fn completed_scan_shows_its_score() {
+    let harness = polling(&["detected", "clean"]);
+    assert!(harness
+        .rendered_text()
+        .contains("1/2 engines flagged this file"));
+}
+
+const MAINTENANCE: &str = r#"{"maintenance": true, "message": "Back at noon"}"#;
+
+#[test]
+fn parse_maintenance_response_needs_the_flag_and_a_503() {
+    let parse = |status: u16, body: &str| {
+        parse_maintenance_response(StatusCode::from_u16(status).unwrap(), body).map(|x| x.message)
+    };
+
+    assert_eq!(parse(503, MAINTENANCE), Some(Some("Back at noon".into())));
+    assert_eq!(parse(503, r#"{"maintenance": true}"#), Some(None));
+    assert_eq!(parse(503, r#"{"maintenance": false}"#), None);
+    assert_eq!(parse(503, "Service Unavailable"), None);
+    assert_eq!(parse(500, MAINTENANCE), None);
+}
+
+#[test]
+fn maintenance_is_entered_and_left() {
+    let mut harness = polling(&["pending"]);
+    harness.send(Msg::FetchTasks(7));
+    harness.respond("/v1/reports/7/tasks", 503, MAINTENANCE);
+
+    assert_eq!(harness.model.scene, Scene::Maintenance);
+    assert!(harness.model.maintenance_it.is_some());
+    assert!(harness.rendered_text().contains("Back at noon"));
+
+    // The retry interval refetches the profiles until the backend is back.
+    harness.send(Msg::FetchProfiles);
+    harness.respond("/v1/profiles", 503, MAINTENANCE);
+    assert_eq!(harness.model.scene, Scene::Maintenance);
+
+    harness.send(Msg::FetchProfiles);
+    harness.respond("/v1/profiles", 200, PROFILES);
+    assert_eq!(harness.model.scene, Scene::LoggedIn);
+    assert!(harness.model.maintenance_it.is_none());
+    assert_eq!(harness.model.state.report_id, Some(7));
+    assert!(harness.model.is_polling());
+}
+
+#[test]
+fn logout_during_a_scan_asks_first() {
+    let mut harness = polling(&["pending"]);
+    harness.send(Msg::ConfirmLogout);
+    assert!(harness
+        .model
+        .modal
+        .as_ref()
+        .unwrap()
+        .body
+        .contains("A scan is still running"));
+
+    harness.click_modal_action("Cancel");
+    assert!(harness.model.modal.is_none());
+    assert!(harness.model.is_polling());
+    assert!(!harness.is_in_flight("/v1/auth/logout"));
+}
+
+#[test]
+fn logout_during_a_scan_stops_polling_and_clears_the_report() {
+    let mut harness = polling(&["pending"]);
+    harness.send(Msg::ConfirmLogout);
+    harness.click_modal_action("Log out anyway");
+    harness.respond("/v1/auth/logout", 204, "");
+
+    assert_eq!(harness.model.scene, Scene::LoginRegister);
+    assert!(!harness.model.is_polling());
+    assert!(harness.model.it.is_none());
+    assert_eq!(harness.model.current_report_id, None);
+    assert!(harness.model.current_pending_tasks.is_none());
+    assert_eq!(harness.model.state.report_id, None);
+}
+
+#[test]
+fn logout_without_a_scan_or_log_does_not_ask() {
+    let mut harness = Harness::logged_in();
+    harness.model.audit_log.clear();
+    harness.send(Msg::ConfirmLogout);
+    assert!(harness.model.modal.is_none());
+    assert!(harness.is_in_flight("/v1/auth/logout"));
+}
+
+/// `polling`, then a second upload that becomes report 8 while report 7 is still running.
+fn polling_two_reports() -> Harness {
+    let mut harness = polling(&["pending"]);
+    harness.upload("other.exe", b"MZ");
+    harness.respond("/v1/reports/create", 200, r#"{"report_id": 8}"#);
+    harness.respond("/v1/reports/8/tasks", 200, &tasks_json(&["pending"]));
+    harness
+}
+
+#[test]
+fn reports_in_other_tabs_keep_being_polled() {
+    let mut harness = polling_two_reports();
+    assert_eq!(harness.model.current_report_id, Some(8));
+    assert_eq!(harness.model.background_report_ids(), [7]);
+    assert!(harness.model.background_it.is_some());
+
+    harness.send(Msg::PollBackgroundReports);
+    assert!(harness.is_in_flight("/v1/reports/7/tasks"));
+    assert!(!harness.is_in_flight("/v1/reports/8/tasks"));
+
+    harness.respond("/v1/reports/7/tasks", 200, &tasks_json(&["clean"]));
+    assert!(harness.model.background_report_ids().is_empty());
+    assert!(harness.model.background_it.is_none());
+    assert_eq!(harness.model.current_report_id, Some(8));
+    assert!(harness.model.is_polling());
+}
+
+#[test]
+fn switching_reports_stops_polling_the_old_one() {
+    let mut harness = polling_two_reports();
+    harness.send(Msg::PollReport(8));
+    assert!(harness.is_in_flight("/v1/reports/8/status"));
+
+    harness.send(Msg::SelectReportTab(7));
+    // The poll in flight for report 8 is dropped and report 7 polled in its place.
+    assert!(!harness.is_in_flight("/v1/reports/8/status"));
+    assert!(harness.is_in_flight("/v1/reports/7/tasks"));
+    assert_eq!(harness.model.current_report_id, Some(7));
+    assert_eq!(harness.model.state.report_id, Some(7));
+    assert_eq!(harness.model.background_report_ids(), [8]);
+
+    harness.respond("/v1/reports/7/tasks", 200, &tasks_json(&["detected"]));
+    assert!(!harness.model.is_polling());
+    assert_eq!(harness.model.report_tasks[&8][0].status, "pending");
+}
+
+#[test]
+fn login_stores_both_tokens() {
+    let harness = Harness::logged_in();
+    let stored: State = serde_json::from_str(&harness.stored(KEY).unwrap()).unwrap();
+    assert_eq!(stored.token.as_deref(), Some("t0k"));
+    assert_eq!(stored.refresh_token.as_deref(), Some("r3f"));
+    assert_eq!(
+        harness.last_request("/v1/profiles").header("Authorization"),
+        Some("t0k")
+    );
+}
+
+#[test]
+fn concurrent_unauthorized_answers_refresh_once() {
+    let mut harness = polling_two_reports();
+    harness.send(Msg::PollReport(8));
+    harness.send(Msg::PollBackgroundReports);
+
+    harness.respond("/v1/reports/8/status", 401, "");
+    harness.respond("/v1/reports/7/tasks", 401, "");
+    assert_eq!(harness.requests("/v1/auth/refresh").len(), 1);
+
+    harness.respond("/v1/auth/refresh", 200, r#"{"token": "n3w"}"#);
+    assert_eq!(harness.model.state.token.as_deref(), Some("n3w"));
+    // The backend did not rotate the refresh token, so the old one stays.
+    assert_eq!(harness.model.state.refresh_token.as_deref(), Some("r3f"));
+    assert_eq!(
+        harness.last_request("/v1/profiles").header("Authorization"),
+        Some("n3w")
+    );
+
+    harness.respond("/v1/profiles", 200, PROFILES);
+    assert_eq!(harness.model.scene, Scene::LoggedIn);
+    assert!(harness.model.is_polling());
+}
+
+#[test]
+fn rotated_refresh_token_replaces_the_old_one() {
+    let mut harness = polling(&["pending"]);
+    harness.send(Msg::FetchTasks(7));
+    harness.respond("/v1/reports/7/tasks", 401, "");
+    harness.respond(
+        "/v1/auth/refresh",
+        200,
+        r#"{"token": "n3w", "refresh_token": "r0t"}"#,
+    );
+
+    let stored: State = serde_json::from_str(&harness.stored(KEY).unwrap()).unwrap();
+    assert_eq!(stored.token.as_deref(), Some("n3w"));
+    assert_eq!(stored.refresh_token.as_deref(), Some("r0t"));
+}
+
+#[test]
+fn single_token_sessions_expire_without_refreshing() {
+    let mut harness = Harness::with_config(CONFIG);
+    harness.send(Msg::Login);
+    harness.respond("/v1/auth/login", 200, r#"{"token": "t0k"}"#);
+    harness.respond("/v1/profiles", 200, PROFILES);
+    assert_eq!(harness.model.state.refresh_token, None);
+
+    harness.send(Msg::FetchProfiles);
+    harness.respond("/v1/profiles", 401, "");
+    assert!(harness.requests("/v1/auth/refresh").is_empty());
+    assert_eq!(harness.model.scene, Scene::LoginRegister);
+}
+
+#[test]
+fn profiles_query_value_encodes_each_name() {
+    assert_eq!(profiles_query_value(vec!["clamav", "yara"]), "clamav,yara");
+    assert_eq!(
+        profiles_query_value(vec!["av,beta", "a&b=c", "x#y"]),
+        "av%2Cbeta,a%26b%3Dc,x%23y"
+    );
+    assert_eq!(profiles_query_value(Vec::new()), "");
+}
+
+#[test]
+fn machine_name_with_a_comma_makes_a_valid_upload() {
+    let profiles = PROFILES.replace(
+        r#""machine_name": "yara""#,
+        r#""machine_name": "yara,strict""#,
+    );
+    let mut harness = Harness::logged_in_with(CONFIG, &profiles);
+    harness.upload("sample.exe", b"MZ");
+
+    let uri: http::Uri = harness
+        .last_request("/v1/reports/create")
+        .uri
+        .parse()
+        .unwrap();
+    let query = uri.query().unwrap();
+    let names: HashSet<&str> = query.trim_start_matches("profiles=").split(',').collect();
+    assert_eq!(names, ["clamav", "yara%2Cstrict"].iter().cloned().collect());
+}
+
+#[test]
+fn unconfirmed_logout_clears_the_session_locally() {
+    let mut harness = polling(&["pending"]);
+    harness.send(Msg::Logout);
+    assert!(harness.model.logout_tt.is_some());
+
+    harness.send(Msg::LogoutTimedOut);
+    assert!(!harness.model.is_logout_loading);
+    assert!(!harness.is_in_flight("/v1/auth/logout"));
+    assert_eq!(harness.model.scene, Scene::LoginRegister);
+    assert_eq!(harness.model.state.token, None);
+    assert!(harness.stored(KEY).unwrap().contains(r#""token":null"#));
+    assert!(!harness.model.is_polling());
+    assert_eq!(
+        harness.model.logout_warning.as_deref(),
+        Some("Logged out here, but the server did not confirm the logout")
+    );
+}
+
+#[test]
+fn logout_timeout_after_the_answer_does_nothing() {
+    let mut harness = Harness::logged_in();
+    harness.send(Msg::Logout);
+    harness.respond("/v1/auth/logout", 204, "");
+    assert!(harness.model.logout_tt.is_none());
+
+    harness.send(Msg::LogoutTimedOut);
+    assert_eq!(harness.model.logout_warning, None);
+}
+
+#[test]
+fn muted_profiles_stay_out_of_every_scan() {
+    let mut harness = Harness::logged_in();
+    harness.send(Msg::ToggleProfileMute(2));
+    assert_eq!(
+        harness.model.enabled_profiles,
+        [1].iter().cloned().collect()
+    );
+
+    harness.send(Msg::ToggleProfile(2));
+    harness.send(Msg::SelectAllProfiles);
+    assert_eq!(
+        harness.model.enabled_profiles,
+        [1].iter().cloned().collect()
+    );
+
+    harness.upload("sample.exe", b"MZ");
+    assert!(harness
+        .last_request("/v1/reports/create")
+        .uri
+        .ends_with("?profiles=clamav"));
+}
+
+#[test]
+fn mutes_are_remembered_and_pruned() {
+    let mut harness = Harness::logged_in();
+    harness.send(Msg::ToggleProfileMute(2));
+    let settings = harness.stored(SETTINGS_KEY).unwrap();
+
+    let harness = restored_with_settings(&settings);
+    assert_eq!(
+        harness.model.settings.muted_profile_ids,
+        [2].iter().cloned().collect()
+    );
+    assert_eq!(
+        harness.model.enabled_profiles,
+        [1].iter().cloned().collect()
+    );
+
+    // A profile the backend removed is unmuted rather than kept forever.
+    let harness = restored_with_settings(r#"{"muted_profile_ids": [2, 9]}"#);
+    assert_eq!(
+        harness.model.settings.muted_profile_ids,
+        [2].iter().cloned().collect()
+    );
+}
+
+#[test]
+fn unmuting_leaves_the_profile_disabled() {
+    let mut harness = Harness::logged_in();
+    harness.send(Msg::ToggleProfileMute(2));
+    harness.send(Msg::ToggleProfileMute(2));
+    assert!(harness.model.settings.muted_profile_ids.is_empty());
+    assert_eq!(
+        harness.model.enabled_profiles,
+        [1].iter().cloned().collect()
+    );
+
+    harness.send(Msg::ToggleProfile(2));
+    assert_eq!(
+        harness.model.enabled_profiles,
+        [1, 2].iter().cloned().collect()
+    );
+}
+
+#[test]
+fn muted_profiles_are_hidden_until_shown() {
+    let mut harness = Harness::logged_in();
+    harness.send(Msg::ToggleProfileMute(2));
+    assert!(!harness.rendered_text().contains("YARA"));
+
+    harness.send(Msg::ToggleShowMutedProfiles);
+    assert!(harness.rendered_text().contains("YARA"));
+}
+
+/// Finds the element with the given `id` in the rendered view.
+fn find_tag<'a>(node: &'a Html<Model<MockApi>>, id: &str) -> Option<&'a VTag<Model<MockApi>>> {
+    match node {
+        VNode::VTag(tag) if tag.attributes.get("id").map(String::as_str) == Some(id) => Some(tag),
+        VNode::VTag(tag) => tag.childs.iter().find_map(|x| find_tag(x, id)),
+        VNode::VList(list) => list.childs.iter().find_map(|x| find_tag(x, id)),
+        VNode::VText(_) | VNode::VComp(_) | VNode::VRef(_) => None,
+    }
+}
+
+#[test]
+fn profile_rows_are_focusable() {
+    let harness = Harness::logged_in();
+    let view = harness.model.view();
+    let row = find_tag(&view, "profile-1").expect("no row for profile 1");
+    assert_eq!(
+        row.attributes.get("tabindex").map(String::as_str),
+        Some("0")
+    );
+}
+
+#[test]
+fn space_and_enter_toggle_only_the_focused_row() {
+    assert!(is_profile_row_toggle_key(" ", true));
+    assert!(is_profile_row_toggle_key("Enter", true));
+    assert!(!is_profile_row_toggle_key("a", true));
+    assert!(!is_profile_row_toggle_key("Tab", true));
+    // The checkbox toggles itself, so the row must not toggle it again.
+    assert!(!is_profile_row_toggle_key(" ", false));
+    assert!(!is_profile_row_toggle_key("Enter", false));
+}
+
+#[test]
+fn reports_are_checked_by_hand_without_auto_poll() {
+    let mut harness = Harness::logged_in_with(
+        r#"{"api_url": "http://api.test", "auto_poll": false}"#,
+        PROFILES,
+    );
+    harness.upload("sample.exe", b"MZ");
+    harness.respond("/v1/reports/create", 200, r#"{"report_id": 7}"#);
+    harness.respond("/v1/reports/7/tasks", 200, &tasks_json(&["pending"]));
+
+    assert!(harness.model.it.is_none());
+    assert!(harness.model.background_it.is_none());
+    assert!(harness.model.is_polling());
+    assert!(harness.rendered_text().contains("Check status"));
+
+    harness.send(Msg::FetchTasks(7));
+    harness.respond("/v1/reports/7/tasks", 200, &tasks_json(&["clean"]));
+    assert!(!harness.model.is_polling());
+    assert!(!harness.rendered_text().contains("Check status"));
+}
+
+#[test]
+fn reports_are_polled_by_default() {
+    let harness = polling(&["pending"]);
+    assert!(harness.model.it.is_some());
+    assert!(harness.model.manual_poll_report_id.is_none());
+}
+
+#[test]
+fn long_profile_lists_move_to_a_header() {
+    let config = config(CONFIG);
+    let base_uri = "http://api.test/v1/reports/create";
+    let fits = "a".repeat(MAX_QUERY_URI_LEN - base_uri.len() - "?profiles=".len());
+
+    let (uri, mut builder) = profiles_request_builder(&config, base_uri, &fits, "id");
+    let request = builder.body(()).unwrap();
+    assert_eq!(uri.len(), MAX_QUERY_URI_LEN);
+    assert_eq!(uri, format!("{}?profiles={}", base_uri, fits));
+    assert!(request.headers().get("X-Profiles").is_none());
+
+    let too_long = format!("{}b", fits);
+    let (uri, mut builder) = profiles_request_builder(&config, base_uri, &too_long, "id");
+    let request = builder.body(()).unwrap();
+    assert_eq!(uri, base_uri);
+    assert_eq!(request.uri(), base_uri);
+    assert_eq!(request.headers()["X-Profiles"], too_long.as_str());
+}
+
+#[test]
+fn severity_rank_puts_detections_first() {
+    let ranked = [
+        "detected", "error", "timeout", "clean", "new", "pending", "mystery", "",
+    ];
+    let ranks: Vec<u8> = ranked.iter().map(|x| severity_rank(x)).collect();
+    assert_eq!(ranks, [0, 1, 1, 2, 3, 3, 4, 4]);
+    assert!(ranks.windows(2).all(|x| x[0] <= x[1]));
+}
+
+#[test]
+fn results_are_grouped_by_severity_until_turned_off() {
+    let mut harness = polling(&["clean", "detected"]);
+    let names = |harness: &Harness| -> Vec<String> {
+        harness
+            .model
+            .visible_profiles()
+            .iter()
+            .map(|x| x.human_name.clone())
+            .collect()
+    };
+    assert_eq!(names(&harness), ["YARA", "ClamAV"]);
+
+    harness.send(Msg::ToggleGroupBySeverity);
+    assert_eq!(names(&harness), ["ClamAV", "YARA"]);
+}
+
+/// Reads back the entries of a bundle in archive order.
+fn read_zip(zip: &[u8]) -> Vec<(String, Vec<u8>)> {
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(zip)).unwrap();
+    (0..archive.len())
+        .map(|i| {
+            let mut entry = archive.by_index(i).unwrap();
+            assert_eq!(entry.compression(), zip::CompressionMethod::Stored);
+            let mut data = Vec::new();
+            std::io::Read::read_to_end(&mut entry, &mut data).unwrap();
+            (entry.name().to_owned(), data)
+        })
+        .collect()
+}
+
+#[test]
+fn bundles_hold_the_file_and_its_verdicts() {
+    let tasks = [task(1, "clean"), task(2, "detected")];
+    let zip = build_bundle("../évil/sample.exe", b"MZ\x90\x00", &tasks).unwrap();
+
+    let entries = read_zip(&zip);
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].0, ".._évil_sample.exe");
+    assert_eq!(entries[0].1, b"MZ\x90\x00");
+    assert_eq!(entries[1].0, "tasks.json");
+    let verdicts: serde_json::Value = serde_json::from_slice(&entries[1].1).unwrap();
+    assert_eq!(verdicts[1]["status"], "detected");
+}
+
+#[test]
+fn bundles_reuse_the_uploaded_bytes() {
+    let mut harness = Harness::logged_in_with(
+        r#"{"api_url": "http://api.test", "features": {"bundle_export": true}}"#,
+        PROFILES,
+    );
+
+    harness.upload("sample.exe", b"MZ");
+    harness.respond("/v1/reports/create", 200, r#"{"report_id": 7}"#);
+    assert_eq!(harness.model.current_bundle_file(), Some(&b"MZ"[..]));
+    harness.respond("/v1/reports/7/tasks", 200, &tasks_json(&["clean", "clean"]));
+    assert!(harness.rendered_text().contains("Download bundle"));
+
+    // A failed upload drops its bytes and leaves the previous report's in place.
+    harness.upload("other.exe", b"PK");
+    harness.respond("/v1/reports/create", 500, "");
+    assert!(harness.model.pending_file.is_none());
+    assert_eq!(harness.model.bundle_file, Some((7, b"MZ".to_vec())));
+
+    // Files over the limit are not kept.
+    harness.upload("large.bin", &vec![0; MAX_BUNDLE_FILE_BYTES + 1]);
+    assert!(harness.model.pending_file.is_none());
+}
+
+#[test]
+fn bundles_are_not_kept_when_disabled() {
+    let mut harness = Harness::logged_in();
+
+    harness.upload("sample.exe", b"MZ");
+    harness.respond("/v1/reports/create", 200, r#"{"report_id": 7}"#);
+    harness.respond("/v1/reports/7/tasks", 200, &tasks_json(&["clean", "clean"]));
+    assert!(harness.model.bundle_file.is_none());
+    assert!(!harness.rendered_text().contains("Download bundle"));
+}
+
+#[test]
+fn relative_api_urls_resolve_against_the_page() {
+    let base_uri = "https://scan.example/app/index.html?tab=1#top";
+    let cases = [
+        ("https://api.example", "https://api.example"),
+        (" http://api.example ", "http://api.example"),
+        ("/api", "https://scan.example/api"),
+        ("api", "https://scan.example/app/api"),
+        ("./api", "https://scan.example/app/api"),
+        ("//api.example/v1", "https://api.example/v1"),
+    ];
+    for (api_url, expected) in &cases {
+        assert_eq!(resolve_api_url(api_url, base_uri), *expected, "{}", api_url);
+    }
+
+    assert_eq!(
+        resolve_api_url("/api", "https://scan.example"),
+        "https://scan.example/api"
+    );
+    assert_eq!(
+        resolve_api_url("api", "https://scan.example"),
+        "https://scan.example/api"
+    );
+    assert_eq!(
+        resolve_api_url("api", "https://scan.example/"),
+        "https://scan.example/api"
+    );
+    assert_eq!(resolve_api_url("/api", ""), "/api");
+}
+
+#[test]
+fn sessions_live_in_memory_without_local_storage() {
+    // There is no browser here, so the storage probe fails like it does in strict privacy modes.
+    let storage = LocalStore::new();
+    assert!(!storage.is_persistent());
+
+    let mut harness = Harness::with_storage(storage);
+    harness.configure(CONFIG);
+    harness.model.loginregister_form.username = "alice".into();
+    harness.send(Msg::Login);
+    harness.respond(
+        "/v1/auth/login",
+        200,
+        r#"{"token": "t0k", "refresh_token": "r3f"}"#,
+    );
+    assert!(harness.stored(KEY).unwrap().contains("t0k"));
+
+    let notice = "does not allow local storage";
+    assert!(harness.rendered_text().contains(notice));
+    harness.send(Msg::DismissStorageNotice);
+    assert!(!harness.rendered_text().contains(notice));
+}
+
+#[test]
+fn endpoint_paths_default_to_v1() {
+    let config = config(CONFIG);
+    assert_eq!(
+        config.create_report_uri(),
+        "http://api.test/v1/reports/create"
+    );
+    assert_eq!(config.tasks_uri(7), "http://api.test/v1/reports/7/tasks");
+    assert_eq!(config.profiles_uri(), "http://api.test/v1/profiles");
+}
+
+#[test]
+fn endpoint_paths_can_be_overridden() {
+    const OVERRIDES: &str = r#"{
+        "api_url": "http://api.test",
+        "create_report_path": "/v2/scans",
+        "tasks_path": "/v2/scans/{report_id}/jobs?report={report_id}",
+        "profiles_path": "/v2/engines"
+    }"#;
+    let config = config(OVERRIDES);
+    assert_eq!(config.create_report_uri(), "http://api.test/v2/scans");
+    assert_eq!(
+        config.tasks_uri(7),
+        "http://api.test/v2/scans/7/jobs?report=7"
+    );
+    assert_eq!(config.profiles_uri(), "http://api.test/v2/engines");
+
+    let mut harness = Harness::with_config(OVERRIDES);
+    harness.model.loginregister_form.username = "alice".into();
+    harness.send(Msg::Login);
+    harness.respond(
+        "/v1/auth/login",
+        200,
+        r#"{"token": "t0k", "refresh_token": "r3f"}"#,
+    );
+    assert!(harness.is_in_flight("/v2/engines"));
+    harness.respond("/v2/engines", 200, PROFILES);
+
+    harness.upload("sample.exe", b"MZ");
+    assert!(harness.is_in_flight("/v2/scans?profiles="));
+    harness.respond("/v2/scans", 200, r#"{"report_id": 7}"#);
+    assert!(harness.is_in_flight("/v2/scans/7/jobs?report=7"));
+}
+
+#[test]
+fn elapsed_time_switches_units_at_the_boundaries() {
+    let now: DateTime<Utc> = "2020-01-02T00:00:00Z".parse().unwrap();
+    let ago = |seconds: i64| format_elapsed(now - chrono::Duration::seconds(seconds), now);
+
+    assert_eq!(ago(-30), "just now");
+    assert_eq!(ago(0), "just now");
+    assert_eq!(ago(4), "just now");
+    assert_eq!(ago(5), "5s ago");
+    assert_eq!(ago(59), "59s ago");
+    assert_eq!(ago(60), "1m ago");
+    assert_eq!(ago(3599), "59m ago");
+    assert_eq!(ago(3600), "1h ago");
+    assert_eq!(ago(86399), "23h ago");
+    assert_eq!(ago(86400), "1d ago");
+    assert_eq!(ago(3 * 86400 + 1), "3d ago");
+}
+
+const IDLE: &str =
+    r#"{"api_url": "http://api.test", "idle_logout_minutes": 15, "idle_snooze_minutes": 60}"#;
+
+/// Logged in with `IDLE`, with the session about to time out.
+fn about_to_idle_out() -> Harness {
+    let mut harness = Harness::logged_in_with(IDLE, PROFILES);
+    assert!(harness.model.idle_it.is_some());
+    harness.model.idle_deadline = Some(Utc::now() + chrono::Duration::seconds(30));
+    harness.send(Msg::IdleTick);
+    assert!(harness.model.is_idle_warning_shown);
+    harness
+}
+
+#[test]
+fn snoozing_extends_the_session() {
+    let mut harness = about_to_idle_out();
+    assert!(harness
+        .rendered_text()
+        .contains("You will be logged out in 0:"));
+
+    // Moving the mouse is not enough once the warning is up.
+    let deadline = harness.model.idle_deadline;
+    harness.send(Msg::UserActivity);
+    assert_eq!(harness.model.idle_deadline, deadline);
+
+    harness.send(Msg::SnoozeIdleLogout);
+    assert!(!harness.model.is_idle_warning_shown);
+    let remaining = harness.model.idle_deadline.unwrap() - Utc::now();
+    assert!(remaining > chrono::Duration::minutes(59));
+    assert!(remaining <= chrono::Duration::minutes(60));
+
+    harness.send(Msg::IdleTick);
+    assert!(!harness.model.is_idle_warning_shown);
+    assert_eq!(harness.model.scene, Scene::LoggedIn);
+}
+
+#[test]
+fn ignored_idle_warnings_log_out() {
+    let mut harness = about_to_idle_out();
+    harness.model.idle_deadline = Some(Utc::now() - chrono::Duration::seconds(1));
+    harness.send(Msg::IdleTick);
+    harness.drain();
+    assert!(harness.is_in_flight("/v1/auth/logout"));
+}
+
+#[test]
+fn engines_apply_to_the_inputs_their_module_scans() {
+    let cases = [
+        // module, applies to files, applies to URLs
+        ("clamav", true, true),
+        ("url", false, true),
+        ("url_reputation", false, true),
+        ("safe_browsing_url", false, true),
+        ("urlhaus", true, true),
+        ("file", true, false),
+        ("file_magic", true, false),
+        ("profile", true, true),
+    ];
+    for (module, file, url) in &cases {
+        assert_eq!(
+            is_module_applicable(module, InputKind::File),
+            *file,
+            "{}",
+            module
+        );
+        assert_eq!(
+            is_module_applicable(module, InputKind::Url),
+            *url,
+            "{}",
+            module
+        );
+    }
+}
+
+#[test]
+fn scene_changes_follow_the_session() {
+    use Scene::*;
+    let all = [
+        Loading,
+        LoginRegister,
+        FetchConfigError,
+        Maintenance,
+        LoggedIn,
+        History,
+    ];
+
+    for &from in &all {
+        // A broken or ended session can always be left.
+        assert!(is_scene_change_allowed(from, LoginRegister, false));
+        assert!(is_scene_change_allowed(from, FetchConfigError, false));
+        for &to in &[Loading, Maintenance, LoggedIn, History] {
+            assert!(
+                !is_scene_change_allowed(from, to, false),
+                "{:?} -> {:?}",
+                from,
+                to
+            );
+        }
+    }
+
+    assert!(is_scene_change_allowed(Loading, LoggedIn, true));
+    assert!(is_scene_change_allowed(LoggedIn, Maintenance, true));
+    assert!(is_scene_change_allowed(LoggedIn, History, true));
+    assert!(is_scene_change_allowed(History, History, true));
+    assert!(is_scene_change_allowed(History, LoggedIn, true));
+    for &from in &[Loading, LoginRegister, FetchConfigError, Maintenance] {
+        assert!(
+            !is_scene_change_allowed(from, History, true),
+            "{:?} -> History",
+            from
+        );
+    }
+}
+
+#[test]
+fn the_server_upload_limit_wins() {
+    let mut harness = Harness::logged_in_with(
+        r#"{"api_url": "http://api.test", "max_upload_bytes": 1000}"#,
+        PROFILES,
+    );
+    assert_eq!(harness.model.max_upload_bytes(), Some(1000));
+
+    harness.respond("/v1/limits", 200, r#"{"max_upload_bytes": 104857600}"#);
+    assert_eq!(harness.model.max_upload_bytes(), Some(104_857_600));
+    let hint = format!("Max {}", format_file_size(104_857_600));
+    assert!(harness.rendered_text().contains(&hint));
+
+    assert_eq!(
+        harness.model.upload_size_error("sample.exe", 104_857_600),
+        None
+    );
+    let error = harness
+        .model
+        .upload_size_error("sample.exe", 104_857_601)
+        .unwrap();
+    assert!(error.starts_with("sample.exe is too large"));
+}
+
+#[test]
+fn the_config_upload_limit_applies_without_a_limits_endpoint() {
+    let mut harness = Harness::logged_in_with(
+        r#"{"api_url": "http://api.test", "max_upload_bytes": 1000}"#,
+        PROFILES,
+    );
+    harness.respond("/v1/limits", 404, "");
+    assert_eq!(harness.model.max_upload_bytes(), Some(1000));
+    assert!(harness
+        .model
+        .upload_size_error("sample.exe", 1001)
+        .is_some());
+
+    let mut harness = Harness::logged_in();
+    harness.respond("/v1/limits", 404, "");
+    assert_eq!(harness.model.max_upload_bytes(), None);
+    assert_eq!(
+        harness.model.upload_size_error("sample.exe", u64::MAX),
+        None
+    );
+    assert!(!harness.rendered_text().contains("Max "));
+}
+
+#[test]
+fn error_rows_show_the_reason_and_retry_their_engine() {
+    let mut harness = polling(&["clean", "error"]);
+    let text = harness.rendered_text();
+    assert!(text.contains("No details were given"));
+    assert!(text.contains("Retry"));
+
+    harness.send(Msg::FetchTasks(7));
+    let tasks = tasks_json(&["clean", "error"]).replace(
+        r#""status": "error", "message": null"#,
+        r#""status": "error", "message": "engine crashed""#,
+    );
+    harness.respond("/v1/reports/7/tasks", 200, &tasks);
+    assert!(harness.rendered_text().contains("engine crashed"));
+
+    harness.send(Msg::RetryTask(2));
+    let retry = harness.last_request("/v1/reports/7/retry");
+    assert!(retry.uri.ends_with("/v1/reports/7/retry?profiles=yara"));
+    assert_eq!(retry.header("Authorization"), Some("t0k"));
+    harness.respond("/v1/reports/7/retry", 204, "");
+    assert!(harness.is_in_flight("/v1/reports/7/tasks"));
+    assert!(harness.model.is_polling());
+}
+
+const AUTO_REFRESH: &str = r#"{"api_url": "http://api.test", "profiles_refresh_interval_s": 60}"#;
+
+#[test]
+fn auto_refresh_reconciles_the_selection() {
+    let mut harness = Harness::logged_in_with(AUTO_REFRESH, PROFILES);
+    assert!(harness.model.profiles_refresh_it.is_some());
+    harness.send(Msg::ToggleProfile(1));
+
+    harness.send(Msg::AutoRefreshProfiles);
+    harness.drain();
+    let profiles = PROFILES.replace(
+        "\n]}",
+        r#",
+        {"id": 3, "machine_name": "sophos", "human_name": "Sophos", "module": "sophos", "config": null}
+    ]}"#,
+    );
+    harness.respond("/v1/profiles", 200, &profiles);
+
+    // The user's choice survives and the new engine shows up without being enabled.
+    assert_eq!(
+        harness.model.enabled_profiles,
+        [2].iter().cloned().collect()
+    );
+    assert!(harness.rendered_text().contains("Sophos"));
+}
+
+#[test]
+fn auto_refresh_waits_for_scans_and_stops_on_logout() {
+    let mut harness = Harness::logged_in_with(AUTO_REFRESH, PROFILES);
+    harness.upload("sample.exe", b"MZ");
+    harness.respond("/v1/reports/create", 200, r#"{"report_id": 7}"#);
+    harness.respond("/v1/reports/7/tasks", 200, &tasks_json(&["pending"]));
+    let fetched = harness.requests("/v1/profiles").len();
+
+    harness.send(Msg::AutoRefreshProfiles);
+    harness.drain();
+    assert_eq!(harness.requests("/v1/profiles").len(), fetched);
+
+    harness.send(Msg::Logout);
+    harness.respond("/v1/auth/logout", 200, "");
+    assert_eq!(harness.model.scene, Scene::LoginRegister);
+    assert!(harness.model.profiles_refresh_it.is_none());
+}
+
+#[test]
+fn request_ids_are_lowercase_hex() {
+    assert_eq!(format_request_id(&[]), "");
+    assert_eq!(
+        format_request_id(&[0x00, 0x0f, 0xa0, 0xff, 0x12, 0x34, 0x56, 0x78]),
+        "000fa0ff12345678"
+    );
+}
+
+#[test]
+fn requests_carry_the_action_request_id() {
+    let mut harness = Harness::logged_in_with(
+        r#"{"api_url": "http://api.test", "extra_headers": {"X-Request-Id": "spoofed"}}"#,
+        PROFILES,
+    );
+    harness.upload("sample.exe", b"MZ");
+    let upload = harness.last_request("/v1/reports/create");
+    let ids: Vec<_> = upload.headers.get_all("X-Request-Id").iter().collect();
+    assert_eq!(ids, [harness.model.request_id.as_str()]);
+}
+
+#[test]
+fn stale_tasks_responses_are_ignored() {
+    let mut harness = polling(&["pending", "pending"]);
+    let stale_seq = harness.model.tasks_fetch_seq;
+
+    harness.send(Msg::FetchTasks(7));
+    harness.respond(
+        "/v1/reports/7/tasks",
+        200,
+        &tasks_json(&["clean", "pending"]),
+    );
+    assert!(harness.model.applied_tasks_seq > stale_seq);
+
+    // An earlier poll that was delayed must not turn the finished task back to pending.
+    let stale = parse_tasks_response(&tasks_json(&["pending", "pending"])).unwrap();
+    assert!(!harness.send(Msg::FetchTasksDone(stale_seq, Ok(stale))));
+    let statuses: Vec<&str> = harness
+        .model
+        .current_pending_tasks
+        .iter()
+        .flatten()
+        .map(|x| x.status.as_str())
+        .collect();
+    assert_eq!(statuses, ["clean", "pending"]);
+}
+
+#[test]
+fn the_config_template_round_trips() {
+    let template = serde_json::to_string_pretty(&Config::template()).unwrap();
+    let parsed: Config = serde_json::from_str(&template).unwrap();
+    assert_eq!(parsed.api_url, "https://api.example.com");
+    assert_eq!(
+        parsed.tasks_uri(7),
+        "https://api.example.com/v1/reports/7/tasks"
+    );
+
+    let original: serde_json::Value = serde_json::from_str(&template).unwrap();
+    assert_eq!(serde_json::to_value(&parsed).unwrap(), original);
+    assert!(original
+        .as_object()
+        .unwrap()
+        .contains_key("idle_snooze_minutes"));
+}
+
+#[test]
+fn definitions_are_stale_past_the_threshold() {
+    let harness = Harness::logged_in();
+    let render = |days_old: i64| {
+        let updated = Utc::now() - chrono::Duration::days(days_old) - chrono::Duration::hours(1);
+        let profile: Profile = serde_json::from_value(serde_json::json!({
+            "id": 1, "machine_name": "clamav", "human_name": "ClamAV", "module": "clamav",
+            "config": null, "definitions_date": updated.to_rfc3339(),
+        }))
+        .unwrap();
+        match harness.model.view_definitions_age(&profile) {
+            VNode::VTag(tag) => (tag.classes.contains("is-warning"), tag),
+            _ => panic!("no definitions tag"),
+        }
+    };
+
+    let (is_stale, tag) = render(DEFAULT_STALE_DEFINITIONS_DAYS);
+    assert!(!is_stale);
+    match &tag.childs[..] {
+        [VNode::VText(text)] => assert_eq!(text.text, "Defs: 7 days old"),
+        _ => panic!("unexpected definitions tag content"),
+    }
+    assert!(render(DEFAULT_STALE_DEFINITIONS_DAYS + 1).0);
+
+    let profile: Profile = serde_json::from_str(
+        r#"{"id": 1, "machine_name": "clamav", "human_name": "ClamAV", "module": "clamav", "config": null}"#,
+    )
+    .unwrap();
+    match harness.model.view_definitions_age(&profile) {
+        VNode::VList(list) => assert!(list.childs.is_empty()),
+        _ => panic!("definitions shown without a date"),
+    }
+}
+
+#[test]
+fn jumping_cycles_through_detections() {
+    let mut harness = polling(&["detected", "detected"]);
+    assert_eq!(harness.model.detected_profile_ids(), [1, 2]);
+
+    for &expected in &[1, 2, 1] {
+        assert!(harness.send(Msg::JumpToDetection));
+        assert_eq!(harness.model.highlighted_profile_id, Some(expected));
+    }
+}
+
+#[test]
+fn there_is_nothing_to_jump_to_without_detections() {
+    let mut harness = polling(&["clean", "error"]);
+    assert!(harness.model.detected_profile_ids().is_empty());
+    assert!(!harness.send(Msg::JumpToDetection));
+    assert_eq!(harness.model.highlighted_profile_id, None);
+}
+
+#[test]
+fn failed_session_writes_warn_but_keep_the_session() {
+    let mut harness = Harness::with_storage(LocalStore::Full);
+    harness.configure(CONFIG);
+    harness.model.loginregister_form.username = "alice".into();
+    harness.send(Msg::Login);
+    harness.respond(
+        "/v1/auth/login",
+        200,
+        r#"{"token": "t0k", "refresh_token": "r3f"}"#,
+    );
+    harness.respond("/v1/profiles", 200, PROFILES);
+
+    assert_eq!(harness.model.scene, Scene::LoggedIn);
+    assert_eq!(harness.model.state.token.as_deref(), Some("t0k"));
+    let warning = "Couldn't save your session";
+    assert!(harness.rendered_text().contains(warning));
+
+    // Uploads still authenticate with the token kept in memory.
+    harness.upload("sample.exe", b"MZ");
+    let upload = harness.last_request("/v1/reports/create");
+    assert_eq!(upload.header("Authorization"), Some("t0k"));
+
+    harness.send(Msg::DismissStorageWriteWarning);
+    assert!(!harness.rendered_text().contains(warning));
+}
+
+#[test]
+fn failed_settings_writes_warn() {
+    let mut harness = Harness::logged_in();
+    harness.model.storage_service = LocalStore::Full;
+    let compact_table = harness.model.settings.compact_table;
+    harness.send(Msg::ToggleDensity);
+    assert_eq!(harness.model.settings.compact_table, !compact_table);
+    assert!(harness
+        .rendered_text()
+        .contains("Couldn't save your settings"));
+}
+
+#[test]
+fn startup_picks_the_scene_from_the_stored_session() {
+    let mut harness = Harness::new();
+    harness.configure(CONFIG);
+    assert_eq!(harness.model.scene, Scene::LoginRegister);
+    assert!(harness.requests("/v1/profiles").is_empty());
+
+    let mut harness = Harness::restored(&[(KEY, r#"{"token": "t0k"}"#)]);
+    harness.configure(CONFIG);
+    assert_eq!(harness.model.scene, Scene::Loading);
+    assert!(harness.is_in_flight("/v1/profiles"));
+}
+
+#[test]
+fn failed_file_reads_are_reported_unless_cancelled() {
+    let mut harness = Harness::logged_in();
+    harness.model.is_file_uploading = true;
+    harness.send(Msg::ReadFileFailed);
+    assert!(!harness.model.is_file_uploading);
+    assert!(harness
+        .rendered_text()
+        .contains("Could not read the selected file"));
+
+    let mut harness = Harness::logged_in();
+    harness.model.is_file_uploading = true;
+    harness.send(Msg::CancelFileRead);
+    // Aborting the read makes the reader fail too, which must not look like an error.
+    assert!(!harness.send(Msg::ReadFileFailed));
+    assert_eq!(harness.model.create_report_error, None);
+    assert!(!harness
+        .rendered_text()
+        .contains("Could not read the selected file"));
+}
+
+#[test]
+fn missing_profiles_render_the_error_fallback() {
+    let mut harness = Harness::logged_in();
+    harness.model.fetched_profiles = None;
+    assert_eq!(harness.model.scene, Scene::LoggedIn);
+
+    let text = harness.rendered_text();
+    assert!(text.contains("Something went wrong"));
+    assert!(text.contains("Reload"));
+    assert!(text.contains("Logout"));
+    assert!(!text.contains("ClamAV"));
+}